@@ -2,6 +2,24 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20251104_161216_create_users;
 mod m20251104_162418_create_products;
+mod m20251110_091500_create_refresh_tokens;
+mod m20251112_140000_create_revoked_tokens;
+mod m20251114_103000_add_role_to_users;
+mod m20251117_120000_create_email_verification;
+mod m20251119_150000_create_password_reset_tokens;
+mod m20251121_093000_create_api_keys;
+mod m20251124_101500_create_mfa;
+mod m20251126_090000_add_pagination_indexes;
+mod m20251128_110000_add_product_search;
+mod m20251201_093000_add_deleted_at_to_products;
+mod m20251203_140000_create_product_images;
+mod m20251205_100000_create_categories_and_tags;
+mod m20251208_090000_create_inventory;
+mod m20251210_150000_create_product_revisions;
+mod m20251211_090000_backfill_product_revisions;
+mod m20251212_090000_unique_product_image_positions;
+
+pub use m20251128_110000_add_product_search::SEARCH_LANGUAGE_SETTING;
 
 pub struct Migrator;
 
@@ -11,6 +29,22 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20251104_161216_create_users::Migration),
             Box::new(m20251104_162418_create_products::Migration),
+            Box::new(m20251110_091500_create_refresh_tokens::Migration),
+            Box::new(m20251112_140000_create_revoked_tokens::Migration),
+            Box::new(m20251114_103000_add_role_to_users::Migration),
+            Box::new(m20251117_120000_create_email_verification::Migration),
+            Box::new(m20251119_150000_create_password_reset_tokens::Migration),
+            Box::new(m20251121_093000_create_api_keys::Migration),
+            Box::new(m20251124_101500_create_mfa::Migration),
+            Box::new(m20251126_090000_add_pagination_indexes::Migration),
+            Box::new(m20251128_110000_add_product_search::Migration),
+            Box::new(m20251201_093000_add_deleted_at_to_products::Migration),
+            Box::new(m20251203_140000_create_product_images::Migration),
+            Box::new(m20251205_100000_create_categories_and_tags::Migration),
+            Box::new(m20251208_090000_create_inventory::Migration),
+            Box::new(m20251210_150000_create_product_revisions::Migration),
+            Box::new(m20251211_090000_backfill_product_revisions::Migration),
+            Box::new(m20251212_090000_unique_product_image_positions::Migration),
         ]
     }
 }
@@ -0,0 +1,93 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20251104_161216_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(timestamp_null(UsersVerification::EmailVerifiedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmailVerificationTokens::Table)
+                    .if_not_exists()
+                    .col(
+                        integer(EmailVerificationTokens::Id)
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(integer(EmailVerificationTokens::UserId).not_null())
+                    .col(text_uniq(EmailVerificationTokens::TokenHash))
+                    .col(timestamp(EmailVerificationTokens::ExpiresAt))
+                    .col(timestamp_null(EmailVerificationTokens::UsedAt))
+                    .col(
+                        timestamp(EmailVerificationTokens::CreatedAt)
+                            .default(Keyword::CurrentTimestamp),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(
+                        EmailVerificationTokens::Table,
+                        EmailVerificationTokens::UserId,
+                    )
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(EmailVerificationTokens::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(UsersVerification::EmailVerifiedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UsersVerification {
+    EmailVerifiedAt,
+}
+
+#[derive(DeriveIden)]
+enum EmailVerificationTokens {
+    Table,
+    Id,
+    UserId,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
@@ -0,0 +1,105 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20251104_161216_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TotpCredentials::Table)
+                    .if_not_exists()
+                    .col(integer(TotpCredentials::Id).auto_increment().primary_key())
+                    .col(integer_uniq(TotpCredentials::UserId))
+                    .col(text(TotpCredentials::Secret))
+                    .col(timestamp_null(TotpCredentials::ConfirmedAt))
+                    .col(big_integer_null(TotpCredentials::LastUsedStep))
+                    .col(timestamp(TotpCredentials::CreatedAt).default(Keyword::CurrentTimestamp))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(TotpCredentials::Table, TotpCredentials::UserId)
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MfaRecoveryCodes::Table)
+                    .if_not_exists()
+                    .col(integer(MfaRecoveryCodes::Id).auto_increment().primary_key())
+                    .col(integer(MfaRecoveryCodes::UserId).not_null())
+                    .col(text(MfaRecoveryCodes::CodeHash))
+                    .col(timestamp_null(MfaRecoveryCodes::UsedAt))
+                    .col(timestamp(MfaRecoveryCodes::CreatedAt).default(Keyword::CurrentTimestamp))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(MfaRecoveryCodes::Table, MfaRecoveryCodes::UserId)
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mfa_recovery_codes_user_id")
+                    .table(MfaRecoveryCodes::Table)
+                    .col(MfaRecoveryCodes::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MfaRecoveryCodes::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TotpCredentials::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TotpCredentials {
+    Table,
+    Id,
+    UserId,
+    Secret,
+    ConfirmedAt,
+    LastUsedStep,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum MfaRecoveryCodes {
+    Table,
+    Id,
+    UserId,
+    CodeHash,
+    UsedAt,
+    CreatedAt,
+}
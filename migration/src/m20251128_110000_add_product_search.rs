@@ -0,0 +1,88 @@
+use std::env;
+
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, DbBackend, Statement},
+};
+
+/// Transaction-local setting through which the server hands its resolved
+/// `search.language` to this migration.
+pub const SEARCH_LANGUAGE_SETTING: &str = "app.search_language";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A generated column needs a constant text search configuration, so the
+        // language is fixed when the migration runs. It is taken from the
+        // server's own setting; the server refuses to start if they differ.
+        let language = search_language(manager).await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                "ALTER TABLE products ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+                    setweight(to_tsvector('{language}', coalesce(title, '')), 'A') ||
+                    setweight(to_tsvector('{language}', coalesce(content, '')), 'B')
+                ) STORED"
+            ))
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE INDEX idx_products_search_vector ON products USING GIN (search_vector)",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DROP INDEX IF EXISTS idx_products_search_vector")
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE products DROP COLUMN IF EXISTS search_vector")
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// The server's `search.language` when it runs the migrations, else
+/// `APP_SEARCH__LANGUAGE` or the older `SEARCH_LANGUAGE` for the standalone
+/// CLI.
+async fn search_language(manager: &SchemaManager<'_>) -> Result<String, DbErr> {
+    let from_server = manager
+        .get_connection()
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            format!("SELECT current_setting('{SEARCH_LANGUAGE_SETTING}', true) AS language"),
+        ))
+        .await?
+        .map(|row| row.try_get::<Option<String>>("", "language"))
+        .transpose()?
+        .flatten()
+        .filter(|language| !language.is_empty());
+
+    let language = match from_server {
+        Some(language) => language,
+        None => env::var("APP_SEARCH__LANGUAGE")
+            .or_else(|_| env::var("SEARCH_LANGUAGE"))
+            .unwrap_or_else(|_| "english".to_string()),
+    };
+
+    if language.is_empty() || !language.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+        return Err(DbErr::Migration(format!(
+            "Invalid search language `{language}`"
+        )));
+    }
+
+    Ok(language)
+}
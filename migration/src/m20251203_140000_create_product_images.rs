@@ -0,0 +1,75 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductImages::Table)
+                    .if_not_exists()
+                    .col(integer(ProductImages::Id).auto_increment().primary_key())
+                    .col(integer(ProductImages::ProductId).not_null())
+                    .col(integer(ProductImages::Position))
+                    .col(text_uniq(ProductImages::StorageKey))
+                    .col(text(ProductImages::ContentType))
+                    .col(big_integer(ProductImages::SizeBytes))
+                    .col(text(ProductImages::Checksum))
+                    .col(timestamp(ProductImages::CreatedAt).default(Keyword::CurrentTimestamp))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(ProductImages::Table, ProductImages::ProductId)
+                    .to(Products::Table, Products::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_images_product_id_position")
+                    .table(ProductImages::Table)
+                    .col(ProductImages::ProductId)
+                    .col(ProductImages::Position)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProductImages::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProductImages {
+    Table,
+    Id,
+    ProductId,
+    Position,
+    StorageKey,
+    ContentType,
+    SizeBytes,
+    Checksum,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Products {
+    Table,
+    Id,
+}
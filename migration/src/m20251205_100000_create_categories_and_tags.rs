@@ -0,0 +1,172 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Categories::Table)
+                    .if_not_exists()
+                    .col(integer(Categories::Id).auto_increment().primary_key())
+                    .col(string_uniq(Categories::Name))
+                    .col(timestamp(Categories::CreatedAt).default(Keyword::CurrentTimestamp))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tags::Table)
+                    .if_not_exists()
+                    .col(integer(Tags::Id).auto_increment().primary_key())
+                    .col(string_uniq(Tags::Name))
+                    .col(timestamp(Tags::CreatedAt).default(Keyword::CurrentTimestamp))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductCategories::Table)
+                    .if_not_exists()
+                    .col(integer(ProductCategories::ProductId))
+                    .col(integer(ProductCategories::CategoryId))
+                    .primary_key(
+                        Index::create()
+                            .col(ProductCategories::ProductId)
+                            .col(ProductCategories::CategoryId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductTags::Table)
+                    .if_not_exists()
+                    .col(integer(ProductTags::ProductId))
+                    .col(integer(ProductTags::TagId))
+                    .primary_key(
+                        Index::create()
+                            .col(ProductTags::ProductId)
+                            .col(ProductTags::TagId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        for (table, column, target) in [
+            (
+                ProductCategories::Table.into_iden(),
+                ProductCategories::ProductId.into_iden(),
+                Products::Table.into_iden(),
+            ),
+            (
+                ProductCategories::Table.into_iden(),
+                ProductCategories::CategoryId.into_iden(),
+                Categories::Table.into_iden(),
+            ),
+            (
+                ProductTags::Table.into_iden(),
+                ProductTags::ProductId.into_iden(),
+                Products::Table.into_iden(),
+            ),
+            (
+                ProductTags::Table.into_iden(),
+                ProductTags::TagId.into_iden(),
+                Tags::Table.into_iden(),
+            ),
+        ] {
+            manager
+                .create_foreign_key(
+                    ForeignKey::create()
+                        .from(table, column)
+                        .to(target, Alias::new("id"))
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        // The primary keys cover lookups by product; filters go the other way.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_categories_category_id")
+                    .table(ProductCategories::Table)
+                    .col(ProductCategories::CategoryId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_tags_tag_id")
+                    .table(ProductTags::Table)
+                    .col(ProductTags::TagId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProductTags::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ProductCategories::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Tags::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Categories::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Categories {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Tags {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ProductCategories {
+    Table,
+    ProductId,
+    CategoryId,
+}
+
+#[derive(DeriveIden)]
+enum ProductTags {
+    Table,
+    ProductId,
+    TagId,
+}
+
+#[derive(DeriveIden)]
+enum Products {
+    Table,
+}
@@ -0,0 +1,141 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Products::Table)
+                    .add_column(integer(Products::Stock).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Overselling is impossible even if a code path forgets to check.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE products ADD CONSTRAINT products_stock_non_negative CHECK (stock >= 0)",
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(InventoryMovements::Table)
+                    .if_not_exists()
+                    .col(
+                        integer(InventoryMovements::Id)
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(integer(InventoryMovements::ProductId).not_null())
+                    .col(integer_null(InventoryMovements::ActorId))
+                    .col(string(InventoryMovements::Reason))
+                    .col(integer(InventoryMovements::Delta))
+                    .col(integer(InventoryMovements::StockAfter))
+                    .col(integer_null(InventoryMovements::ReservationId).unique_key())
+                    .col(text_null(InventoryMovements::Note))
+                    .col(
+                        timestamp(InventoryMovements::CreatedAt).default(Keyword::CurrentTimestamp),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(InventoryMovements::Table, InventoryMovements::ProductId)
+                    .to(Products::Table, Products::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(InventoryMovements::Table, InventoryMovements::ActorId)
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        // A release points at the reservation it undoes; the unique key on
+        // `reservation_id` means each reservation is released at most once.
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(InventoryMovements::Table, InventoryMovements::ReservationId)
+                    .to(InventoryMovements::Table, InventoryMovements::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_inventory_movements_product_id_created_at")
+                    .table(InventoryMovements::Table)
+                    .col(InventoryMovements::ProductId)
+                    .col(InventoryMovements::CreatedAt)
+                    .col(InventoryMovements::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(InventoryMovements::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Products::Table)
+                    .drop_column(Products::Stock)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InventoryMovements {
+    Table,
+    Id,
+    ProductId,
+    ActorId,
+    Reason,
+    Delta,
+    StockAfter,
+    ReservationId,
+    Note,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Products {
+    Table,
+    Id,
+    Stock,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
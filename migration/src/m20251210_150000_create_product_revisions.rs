@@ -0,0 +1,91 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductRevisions::Table)
+                    .if_not_exists()
+                    .col(integer(ProductRevisions::Id).auto_increment().primary_key())
+                    .col(integer(ProductRevisions::ProductId).not_null())
+                    .col(integer_null(ProductRevisions::ActorId))
+                    .col(string(ProductRevisions::Action))
+                    .col(json_binary(ProductRevisions::Changes))
+                    .col(json_binary(ProductRevisions::Snapshot))
+                    .col(timestamp(ProductRevisions::CreatedAt).default(Keyword::CurrentTimestamp))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(ProductRevisions::Table, ProductRevisions::ProductId)
+                    .to(Products::Table, Products::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(ProductRevisions::Table, ProductRevisions::ActorId)
+                    .to(Users::Table, Users::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_revisions_product_id_created_at")
+                    .table(ProductRevisions::Table)
+                    .col(ProductRevisions::ProductId)
+                    .col(ProductRevisions::CreatedAt)
+                    .col(ProductRevisions::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProductRevisions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProductRevisions {
+    Table,
+    Id,
+    ProductId,
+    ActorId,
+    Action,
+    Changes,
+    Snapshot,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Products {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
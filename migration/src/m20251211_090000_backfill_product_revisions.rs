@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+/// Gives every product a `created` revision. Products that existed before
+/// revisions were recorded had none, so their history began at their first
+/// edit and earlier points in time could not be viewed. The baseline is the
+/// state before the product's first revision, rebuilt from that revision's
+/// `old` values, or its current state when it was never changed.
+const BACKFILL_SQL: &str = r#"
+WITH baseline AS (
+    SELECT
+        p.id AS product_id,
+        p.owner_id,
+        p.created_at,
+        COALESCE(
+            (
+                SELECT r.snapshot || COALESCE(
+                    (SELECT jsonb_object_agg(c.key, c.value -> 'old') FROM jsonb_each(r.changes) c),
+                    '{}'::jsonb
+                )
+                FROM product_revisions r
+                WHERE r.product_id = p.id
+                ORDER BY r.created_at, r.id
+                LIMIT 1
+            ),
+            jsonb_build_object(
+                'title', p.title,
+                'content', p.content,
+                'price', p.price::text,
+                'category_ids', COALESCE(
+                    (
+                        SELECT jsonb_agg(pc.category_id ORDER BY pc.category_id)
+                        FROM product_categories pc
+                        WHERE pc.product_id = p.id
+                    ),
+                    '[]'::jsonb
+                ),
+                'tags', COALESCE(
+                    (
+                        SELECT jsonb_agg(t.name ORDER BY t.name)
+                        FROM product_tags pt
+                        JOIN tags t ON t.id = pt.tag_id
+                        WHERE pt.product_id = p.id
+                    ),
+                    '[]'::jsonb
+                ),
+                'deleted', p.deleted_at IS NOT NULL
+            )
+        ) AS snapshot
+    FROM products p
+    WHERE NOT EXISTS (
+        SELECT 1 FROM product_revisions r WHERE r.product_id = p.id AND r.action = 'created'
+    )
+)
+INSERT INTO product_revisions (product_id, actor_id, action, changes, snapshot, created_at)
+SELECT
+    product_id,
+    owner_id,
+    'created',
+    COALESCE(
+        (
+            SELECT jsonb_object_agg(s.key, jsonb_build_object('old', NULL, 'new', s.value))
+            FROM jsonb_each(snapshot) s
+            WHERE s.value <> 'null'::jsonb
+        ),
+        '{}'::jsonb
+    ),
+    snapshot,
+    created_at
+FROM baseline
+"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(BACKFILL_SQL)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // The baseline revisions are indistinguishable from recorded ones and
+        // harmless to keep.
+        Ok(())
+    }
+}
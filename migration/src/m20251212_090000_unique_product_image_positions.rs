@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+/// Renumbers images that concurrent uploads gave the same position, keeping
+/// their upload order, so the index below can be unique.
+const RENUMBER_SQL: &str = r#"
+UPDATE product_images i
+SET position = ranked.position
+FROM (
+    SELECT id, (row_number() OVER (PARTITION BY product_id ORDER BY position, id) - 1)::int AS position
+    FROM product_images
+) ranked
+WHERE i.id = ranked.id AND i.position <> ranked.position
+"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(RENUMBER_SQL)
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_product_images_product_id_position")
+                    .table(ProductImages::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_images_product_id_position")
+                    .table(ProductImages::Table)
+                    .col(ProductImages::ProductId)
+                    .col(ProductImages::Position)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_product_images_product_id_position")
+                    .table(ProductImages::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_images_product_id_position")
+                    .table(ProductImages::Table)
+                    .col(ProductImages::ProductId)
+                    .col(ProductImages::Position)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProductImages {
+    Table,
+    ProductId,
+    Position,
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use serde::Deserialize;
+
+#[derive(Clone)]
+pub struct AccountConfig {
+    pub app_url: String,
+    pub email_verification_ttl_hours: i64,
+    pub password_reset_ttl_minutes: i64,
+    pub require_verified_email: bool,
+    pub totp_issuer: String,
+    pub mfa_token_ttl_minutes: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountSettings {
+    /// Public base URL used in links sent by email.
+    pub app_url: String,
+    pub email_verification_ttl_hours: i64,
+    pub password_reset_ttl_minutes: i64,
+    pub require_verified_email: bool,
+    /// Issuer label shown in authenticator apps for TOTP enrollment.
+    pub totp_issuer: String,
+    pub mfa_token_ttl_minutes: i64,
+}
+
+impl Default for AccountSettings {
+    fn default() -> Self {
+        AccountSettings {
+            app_url: "http://localhost:3000".to_string(),
+            email_verification_ttl_hours: 24,
+            password_reset_ttl_minutes: 30,
+            require_verified_email: false,
+            totp_issuer: "axum-sea".to_string(),
+            mfa_token_ttl_minutes: 5,
+        }
+    }
+}
+
+impl AccountSettings {
+    pub(super) fn validate(&self, problems: &mut Vec<String>) {
+        if !self.app_url.starts_with("http://") && !self.app_url.starts_with("https://") {
+            problems.push(format!(
+                "account.app_url `{}` must be an http(s) URL",
+                self.app_url
+            ));
+        }
+        for (name, ttl) in [
+            (
+                "account.email_verification_ttl_hours",
+                self.email_verification_ttl_hours,
+            ),
+            (
+                "account.password_reset_ttl_minutes",
+                self.password_reset_ttl_minutes,
+            ),
+            ("account.mfa_token_ttl_minutes", self.mfa_token_ttl_minutes),
+        ] {
+            if ttl <= 0 {
+                problems.push(format!("{name} must be positive"));
+            }
+        }
+    }
+}
+
+pub fn load_account_config(settings: &AccountSettings) -> Result<AccountConfig> {
+    let account_config = AccountConfig {
+        app_url: settings.app_url.trim_end_matches('/').to_string(),
+        email_verification_ttl_hours: settings.email_verification_ttl_hours,
+        password_reset_ttl_minutes: settings.password_reset_ttl_minutes,
+        require_verified_email: settings.require_verified_email,
+        totp_issuer: settings.totp_issuer.clone(),
+        mfa_token_ttl_minutes: settings.mfa_token_ttl_minutes,
+    };
+
+    Ok(account_config)
+}
@@ -1,16 +1,147 @@
-use anyhow::Result;
-use sea_orm::{ConnectOptions, Database, DatabaseConnection};
-use std::{env, time::Duration};
+use anyhow::{Result, bail};
+use migration::{Migrator, MigratorTrait, SEARCH_LANGUAGE_SETTING};
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement,
+    TransactionTrait,
+};
+use serde::Deserialize;
+use std::time::Duration;
 
-pub async fn establish_connection() -> Result<DatabaseConnection> {
-    let mut opt = ConnectOptions::new(
-        env::var("DATABASE_URL").expect("DATABASE_URL must be set in environment variables"),
-    );
+use crate::modules::health::health_service::HealthService;
 
-    opt.max_connections(5)
-        .connect_timeout(Duration::from_secs(8));
+/// Advisory lock id serializing boot-time migrations across replicas.
+const MIGRATION_LOCK_KEY: i64 = 0x6178_756d_5f6d_6967;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseSettings {
+    pub url: String,
+    pub max_connections: u32,
+    pub connect_timeout_secs: u64,
+    /// Apply pending migrations at startup instead of refusing to start.
+    pub run_migrations: bool,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        DatabaseSettings {
+            url: String::new(),
+            max_connections: 5,
+            connect_timeout_secs: 8,
+            run_migrations: false,
+        }
+    }
+}
+
+impl DatabaseSettings {
+    pub(super) fn validate(&self, problems: &mut Vec<String>) {
+        if self.url.is_empty() {
+            problems.push("database.url is required (APP_DATABASE__URL)".to_string());
+        }
+        if self.max_connections == 0 {
+            problems.push("database.max_connections must be at least 1".to_string());
+        }
+    }
+}
+
+pub async fn establish_connection(settings: &DatabaseSettings) -> Result<DatabaseConnection> {
+    let mut opt = ConnectOptions::new(&settings.url);
+
+    opt.max_connections(settings.max_connections)
+        .connect_timeout(Duration::from_secs(settings.connect_timeout_secs));
 
     let db = Database::connect(opt).await?;
 
     Ok(db)
 }
+
+/// Makes sure the schema matches `migration::Migrator` before the server
+/// takes traffic: applies pending migrations when `run_migrations` is set,
+/// and fails otherwise. `search_language` is the resolved `search.language`,
+/// which the search migration bakes into `products.search_vector`.
+pub async fn prepare_schema(
+    db: &DatabaseConnection,
+    settings: &DatabaseSettings,
+    search_language: &str,
+) -> Result<()> {
+    if settings.run_migrations {
+        return run_migrations(db, search_language).await;
+    }
+
+    // Read-only, so a server told not to migrate never writes to the schema.
+    let pending = HealthService::pending_migrations(db).await?;
+    if !pending.is_empty() {
+        bail!(
+            "The database schema is behind, pending migrations: {}. Apply them with the \
+             migration CLI or set database.run_migrations = true",
+            pending.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Runs in one transaction holding a transaction-scoped advisory lock, so
+/// replicas starting together apply each migration once, and the lock is
+/// released even if this process dies halfway.
+async fn run_migrations(db: &DatabaseConnection, search_language: &str) -> Result<()> {
+    let txn = db.begin().await?;
+
+    tracing::info!("Waiting for the migration lock");
+    txn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_advisory_xact_lock($1)",
+        [MIGRATION_LOCK_KEY.into()],
+    ))
+    .await?;
+
+    txn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT set_config($1, $2, true)",
+        [SEARCH_LANGUAGE_SETTING.into(), search_language.into()],
+    ))
+    .await?;
+
+    let pending = Migrator::get_pending_migrations(&txn).await?;
+    if pending.is_empty() {
+        tracing::info!("Database schema is up to date");
+        return Ok(txn.commit().await?);
+    }
+
+    tracing::info!("Applying {} pending migrations", pending.len());
+    Migrator::up(&txn, None).await?;
+    txn.commit().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn test_concurrent_boots_migrate_once() {
+        let url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let db = Database::connect(url).await.unwrap();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let db = db.clone();
+                tokio::spawn(async move { run_migrations(&db, "english").await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        assert!(
+            Migrator::get_pending_migrations(&db)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
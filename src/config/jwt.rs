@@ -1,16 +1,115 @@
-use anyhow::Result;
-use std::env;
+use anyhow::{Context, Result, bail};
+use jsonwebtoken::Algorithm;
+use serde::Deserialize;
+use std::{fs, str::FromStr};
 
-use crate::utils::auth::JwtConfig;
+use crate::utils::{auth::JwtConfig, jwt_key::JwtKey};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtSettings {
+    /// "kid:ALG:public.pem[:private.pem],..."; keys without a private part
+    /// only verify, so retired keys can stay listed until their tokens expire.
+    pub keys: String,
+    pub secret: Option<String>,
+    pub secret_kid: String,
+    /// Defaults to the first configured key.
+    pub signing_kid: Option<String>,
+    pub issuer: String,
+    pub access_token_ttl_minutes: i64,
+    pub refresh_token_ttl_days: i64,
+}
+
+impl Default for JwtSettings {
+    fn default() -> Self {
+        JwtSettings {
+            keys: String::new(),
+            secret: None,
+            secret_kid: "default".to_string(),
+            signing_kid: None,
+            issuer: "my_app".to_string(),
+            access_token_ttl_minutes: 60,
+            refresh_token_ttl_days: 30,
+        }
+    }
+}
+
+impl JwtSettings {
+    pub(super) fn validate(&self, problems: &mut Vec<String>) {
+        if self.keys.trim().is_empty() && self.secret.is_none() {
+            problems.push(
+                "jwt.secret or jwt.keys is required (APP_JWT__SECRET or APP_JWT__KEYS)".to_string(),
+            );
+        }
+        if self.secret.as_deref() == Some("") {
+            problems.push("jwt.secret must not be empty".to_string());
+        }
+        if self.access_token_ttl_minutes <= 0 {
+            problems.push("jwt.access_token_ttl_minutes must be positive".to_string());
+        }
+        if self.refresh_token_ttl_days <= 0 {
+            problems.push("jwt.refresh_token_ttl_days must be positive".to_string());
+        }
+    }
+}
+
+pub fn load_jwt_config(settings: &JwtSettings) -> Result<JwtConfig> {
+    let mut keys = Vec::new();
+
+    for entry in settings
+        .keys
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+    {
+        keys.push(load_pem_key(entry)?);
+    }
+
+    if let Some(secret) = &settings.secret {
+        keys.push(JwtKey::hmac(settings.secret_kid.clone(), secret.as_bytes()));
+    }
+
+    if keys.is_empty() {
+        bail!("Either jwt.keys or jwt.secret must be set");
+    }
+
+    let signing_kid = settings
+        .signing_kid
+        .clone()
+        .unwrap_or_else(|| keys[0].kid.clone());
 
-pub fn load_jwt_config() -> Result<JwtConfig> {
-    let jwt_secret =
-        env::var("JWT_SECRET").expect("JWT_SECRET must be set in environment variables");
     let jwt_config = JwtConfig {
-        secret: jwt_secret,
-        issuer: "my_app".to_string(),
-        access_token_ttl_minutes: 60,
+        keys,
+        signing_kid,
+        issuer: settings.issuer.clone(),
+        access_token_ttl_minutes: settings.access_token_ttl_minutes,
+        refresh_token_ttl_days: settings.refresh_token_ttl_days,
     };
 
+    jwt_config.signing_key()?;
+
     Ok(jwt_config)
 }
+
+fn load_pem_key(entry: &str) -> Result<JwtKey> {
+    let parts: Vec<&str> = entry.split(':').collect();
+    let (kid, alg, public_path, private_path) = match parts.as_slice() {
+        [kid, alg, public_path] => (*kid, *alg, *public_path, None),
+        [kid, alg, public_path, private_path] => (*kid, *alg, *public_path, Some(*private_path)),
+        _ => bail!("Invalid jwt.keys entry `{entry}`, expected kid:ALG:public.pem[:private.pem]"),
+    };
+
+    let algorithm =
+        Algorithm::from_str(alg).with_context(|| format!("Unknown JWT algorithm `{alg}`"))?;
+
+    let public_pem = fs::read_to_string(public_path)
+        .with_context(|| format!("Failed to read JWT public key `{public_path}`"))?;
+    let private_pem = private_path
+        .map(|path| {
+            fs::read_to_string(path)
+                .with_context(|| format!("Failed to read JWT private key `{path}`"))
+        })
+        .transpose()?;
+
+    JwtKey::from_pem(kid, algorithm, &public_pem, private_pem.as_deref())
+}
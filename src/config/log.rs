@@ -0,0 +1,56 @@
+use anyhow::Result;
+use serde::Deserialize;
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Multi-line, human readable output for development.
+    #[default]
+    Pretty,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogSettings {
+    pub format: LogFormat,
+    /// `tracing_subscriber::EnvFilter` directives, e.g. `info,axum_sea=debug`.
+    pub filter: String,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        LogSettings {
+            format: LogFormat::default(),
+            filter: "info,sqlx=warn".to_string(),
+        }
+    }
+}
+
+impl LogSettings {
+    pub(super) fn validate(&self, problems: &mut Vec<String>) {
+        if let Err(e) = EnvFilter::try_new(&self.filter) {
+            problems.push(format!("Invalid log.filter `{}`: {e}", self.filter));
+        }
+    }
+}
+
+pub fn init_tracing(settings: &LogSettings) -> Result<()> {
+    let registry = tracing_subscriber::registry().with(EnvFilter::try_new(&settings.filter)?);
+
+    match settings.format {
+        LogFormat::Pretty => registry.with(fmt::layer().pretty()).try_init()?,
+        LogFormat::Json => registry
+            .with(
+                fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_span_list(false),
+            )
+            .try_init()?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::mailer::{Mailer, file::FileMailer, smtp::SmtpMailer};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailTransport {
+    #[default]
+    Stdout,
+    File,
+    Smtp,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailSettings {
+    pub transport: MailTransport,
+    /// Required with the `smtp` transport.
+    pub smtp_url: Option<String>,
+    pub from: String,
+    /// Where the `file` transport writes messages.
+    pub file_dir: String,
+}
+
+impl Default for MailSettings {
+    fn default() -> Self {
+        MailSettings {
+            transport: MailTransport::default(),
+            smtp_url: None,
+            from: "no-reply@localhost".to_string(),
+            file_dir: "mail".to_string(),
+        }
+    }
+}
+
+impl MailSettings {
+    pub(super) fn validate(&self, problems: &mut Vec<String>) {
+        if self.transport == MailTransport::Smtp && self.smtp_url.is_none() {
+            problems.push(
+                "mail.smtp_url is required when mail.transport is smtp (APP_MAIL__SMTP_URL)"
+                    .to_string(),
+            );
+        }
+    }
+}
+
+pub fn load_mailer(settings: &MailSettings) -> Result<Arc<dyn Mailer>> {
+    let mailer: Arc<dyn Mailer> = match settings.transport {
+        MailTransport::Smtp => {
+            let url = settings
+                .smtp_url
+                .as_deref()
+                .context("mail.smtp_url must be set when mail.transport is smtp")?;
+            Arc::new(SmtpMailer::new(url, &settings.from)?)
+        }
+        MailTransport::File => Arc::new(FileMailer::directory(&settings.file_dir)),
+        MailTransport::Stdout => Arc::new(FileMailer::stdout()),
+    };
+
+    Ok(mailer)
+}
@@ -0,0 +1,64 @@
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use serde::Deserialize;
+
+use crate::utils::metrics::describe_metrics;
+
+/// Upper bounds in seconds, from a fast cache hit to a stuck request.
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    /// Internal address serving `/metrics`, kept off the public listener.
+    pub listen: String,
+    /// Serve `/metrics` on the public listener instead of `listen`. It is
+    /// unauthenticated and reveals routes, error and login failure rates,
+    /// so only opt in when something in front restricts access.
+    pub public: bool,
+    /// How often the connection pool gauges are sampled.
+    pub pool_sample_interval_secs: u64,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        MetricsSettings {
+            enabled: true,
+            listen: "127.0.0.1:9090".to_string(),
+            public: false,
+            pool_sample_interval_secs: 15,
+        }
+    }
+}
+
+impl MetricsSettings {
+    pub(super) fn validate(&self, problems: &mut Vec<String>) {
+        if !self.public && self.listen.parse::<SocketAddr>().is_err() {
+            problems.push(format!(
+                "metrics.listen `{}` must be an address like 127.0.0.1:9090",
+                self.listen
+            ));
+        }
+        if self.pool_sample_interval_secs == 0 {
+            problems.push("metrics.pool_sample_interval_secs must be at least 1".to_string());
+        }
+    }
+}
+
+/// Installs the global Prometheus recorder. Without it every metric macro is
+/// a no-op, which is what `enabled = false` relies on.
+pub fn install_recorder() -> Result<PrometheusHandle> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("duration_seconds".to_string()),
+            &LATENCY_BUCKETS,
+        )?
+        .install_recorder()?;
+    describe_metrics();
+    Ok(handle)
+}
@@ -1,2 +1,12 @@
+pub mod account;
 pub mod db;
 pub mod jwt;
+pub mod log;
+pub mod mail;
+pub mod metrics;
+pub mod product;
+pub mod search;
+pub mod server;
+pub mod settings;
+pub mod storage;
+pub mod throttle;
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use serde::Deserialize;
+
+#[derive(Clone)]
+pub struct ProductConfig {
+    pub trash_retention_days: i64,
+    pub max_image_bytes: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProductSettings {
+    /// Days a deleted product stays in the trash before it is purged.
+    pub trash_retention_days: i64,
+    pub max_image_bytes: usize,
+}
+
+impl Default for ProductSettings {
+    fn default() -> Self {
+        ProductSettings {
+            trash_retention_days: 30,
+            max_image_bytes: 5 * 1024 * 1024,
+        }
+    }
+}
+
+impl ProductSettings {
+    pub(super) fn validate(&self, problems: &mut Vec<String>) {
+        if self.trash_retention_days < 0 {
+            problems.push("product.trash_retention_days must not be negative".to_string());
+        }
+        if self.max_image_bytes == 0 {
+            problems.push("product.max_image_bytes must be at least 1".to_string());
+        }
+    }
+}
+
+pub fn load_product_config(settings: &ProductSettings) -> Result<ProductConfig> {
+    Ok(ProductConfig {
+        trash_retention_days: settings.trash_retention_days,
+        max_image_bytes: settings.max_image_bytes,
+    })
+}
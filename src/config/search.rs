@@ -0,0 +1,99 @@
+use anyhow::{Result, bail};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde::Deserialize;
+
+const SEARCH_VECTOR_EXPRESSION_SQL: &str = r#"
+SELECT pg_get_expr(d.adbin, d.adrelid) AS expression
+FROM pg_attrdef d
+JOIN pg_attribute a ON a.attrelid = d.adrelid AND a.attnum = d.adnum
+WHERE d.adrelid = 'products'::regclass AND a.attname = 'search_vector'
+"#;
+
+#[derive(Clone)]
+pub struct SearchConfig {
+    pub language: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchSettings {
+    /// Postgres text search configuration. The search migration bakes it
+    /// into `products.search_vector`; see `verify_search_language`.
+    pub language: String,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        SearchSettings {
+            language: "english".to_string(),
+        }
+    }
+}
+
+impl SearchSettings {
+    pub(super) fn validate(&self, problems: &mut Vec<String>) {
+        if !is_valid_language(&self.language) {
+            problems.push(format!("Invalid search.language `{}`", self.language));
+        }
+    }
+}
+
+pub fn load_search_config(settings: &SearchSettings) -> Result<SearchConfig> {
+    if !is_valid_language(&settings.language) {
+        bail!("Invalid search.language `{}`", settings.language);
+    }
+
+    Ok(SearchConfig {
+        language: settings.language.clone(),
+    })
+}
+
+/// Fails when `products.search_vector` was generated with a different text
+/// search configuration, which would silently break stemming and ranking.
+/// Call it once the schema is up to date.
+pub async fn verify_search_language(db: &DatabaseConnection, config: &SearchConfig) -> Result<()> {
+    let Some(row) = db
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            SEARCH_VECTOR_EXPRESSION_SQL,
+        ))
+        .await?
+    else {
+        bail!("products.search_vector is missing; has the search migration run?");
+    };
+    let expression: String = row.try_get("", "expression")?;
+
+    match generated_language(&expression) {
+        Some(language) if language == config.language => Ok(()),
+        Some(language) => bail!(
+            "search.language is `{}`, but products.search_vector was generated with `{language}`. \
+             Set search.language to `{language}`, or regenerate the column",
+            config.language
+        ),
+        None => bail!("Unexpected products.search_vector expression: {expression}"),
+    }
+}
+
+/// The configuration in `to_tsvector('english'::regconfig, ...)`.
+fn generated_language(expression: &str) -> Option<&str> {
+    expression.split("to_tsvector('").nth(1)?.split('\'').next()
+}
+
+fn is_valid_language(language: &str) -> bool {
+    !language.is_empty() && language.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_language() {
+        let expression = "(setweight(to_tsvector('simple'::regconfig, COALESCE(title, ''::text)), \
+                          'A'::\"char\") || setweight(to_tsvector('simple'::regconfig, \
+                          COALESCE(content, ''::text)), 'B'::\"char\"))";
+
+        assert_eq!(generated_language(expression), Some("simple"));
+        assert_eq!(generated_language("to_tsvector(title)"), None);
+    }
+}
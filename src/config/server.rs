@@ -0,0 +1,36 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSettings {
+    pub host: String,
+    pub port: u16,
+    /// How long `/health/ready` reports draining before the listener closes,
+    /// so load balancers stop routing here while it still accepts requests.
+    pub shutdown_delay_secs: u64,
+    /// How long in-flight requests may keep running after SIGTERM/SIGINT.
+    pub shutdown_timeout_secs: u64,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        ServerSettings {
+            host: "0.0.0.0".to_string(),
+            port: 3000,
+            shutdown_delay_secs: 5,
+            shutdown_timeout_secs: 30,
+        }
+    }
+}
+
+impl ServerSettings {
+    pub fn bind_address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    pub(super) fn validate(&self, problems: &mut Vec<String>) {
+        if self.host.trim().is_empty() {
+            problems.push("server.host must not be empty".to_string());
+        }
+    }
+}
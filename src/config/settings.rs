@@ -0,0 +1,322 @@
+use std::{env, fmt, fs, path::PathBuf};
+
+use figment::{
+    Figment,
+    providers::{Format, Serialized, Toml},
+    value::Dict,
+};
+use serde::de::DeserializeOwned;
+
+use super::{
+    account::AccountSettings, db::DatabaseSettings, jwt::JwtSettings, log::LogSettings,
+    mail::MailSettings, metrics::MetricsSettings, product::ProductSettings, search::SearchSettings,
+    server::ServerSettings, storage::StorageSettings, throttle::ThrottleSettings,
+};
+
+const ENV_PREFIX: &str = "APP_";
+/// Points at the TOML file; `config.toml` is read when present otherwise.
+const CONFIG_PATH_VAR: &str = "APP_CONFIG";
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+const SECTIONS: [&str; 11] = [
+    "server", "log", "metrics", "database", "jwt", "account", "mail", "throttle", "search",
+    "product", "storage",
+];
+
+/// Every setting the server reads, loaded once at startup from (lowest to
+/// highest precedence) built-in defaults, an optional TOML file and `APP_`
+/// environment variables. Nested keys use `__` in variable names, e.g.
+/// `APP_DATABASE__URL` for `[database] url`, and `APP_<KEY>_FILE` reads the
+/// value from a file instead, for secrets mounted by an orchestrator.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub server: ServerSettings,
+    pub log: LogSettings,
+    pub metrics: MetricsSettings,
+    pub database: DatabaseSettings,
+    pub jwt: JwtSettings,
+    pub account: AccountSettings,
+    pub mail: MailSettings,
+    pub throttle: ThrottleSettings,
+    pub search: SearchSettings,
+    pub product: ProductSettings,
+    pub storage: StorageSettings,
+}
+
+/// Everything wrong with the configuration, so a deploy can fix it in one go.
+#[derive(Debug, thiserror::Error)]
+pub struct SettingsError {
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for problem in &self.problems {
+            write!(f, "\n  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Settings {
+    pub fn load() -> Result<Settings, SettingsError> {
+        let mut problems = Vec::new();
+
+        let config_path = env::var(CONFIG_PATH_VAR).ok().map(PathBuf::from);
+        if let Some(path) = &config_path
+            && !path.is_file()
+        {
+            problems.push(format!(
+                "{CONFIG_PATH_VAR} points at `{}`, which is not a file",
+                path.display()
+            ));
+        }
+
+        let mut figment = Figment::new().merge(Toml::file(
+            config_path.unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH)),
+        ));
+
+        for (key, value) in env_overrides(env::vars(), &mut problems) {
+            figment = figment.merge(Serialized::default(&key, value));
+        }
+
+        Self::from_figment(&figment, problems)
+    }
+
+    /// Extracts each section on its own so one malformed value does not hide
+    /// problems elsewhere, then validates the result as a whole.
+    fn from_figment(
+        figment: &Figment,
+        mut problems: Vec<String>,
+    ) -> Result<Settings, SettingsError> {
+        // Catches typos and keys left over from older releases in the TOML
+        // file, which would otherwise be ignored silently.
+        if let Ok(sections) = figment.extract::<Dict>() {
+            for name in sections
+                .keys()
+                .filter(|name| !SECTIONS.contains(&name.as_str()))
+            {
+                problems.push(format!("Unknown setting `{name}`"));
+            }
+        }
+
+        let settings = Settings {
+            server: section(figment, "server", &mut problems),
+            log: section(figment, "log", &mut problems),
+            metrics: section(figment, "metrics", &mut problems),
+            database: section(figment, "database", &mut problems),
+            jwt: section(figment, "jwt", &mut problems),
+            account: section(figment, "account", &mut problems),
+            mail: section(figment, "mail", &mut problems),
+            throttle: section(figment, "throttle", &mut problems),
+            search: section(figment, "search", &mut problems),
+            product: section(figment, "product", &mut problems),
+            storage: section(figment, "storage", &mut problems),
+        };
+
+        settings.server.validate(&mut problems);
+        settings.log.validate(&mut problems);
+        settings.metrics.validate(&mut problems);
+        settings.database.validate(&mut problems);
+        settings.jwt.validate(&mut problems);
+        settings.account.validate(&mut problems);
+        settings.mail.validate(&mut problems);
+        settings.search.validate(&mut problems);
+        settings.product.validate(&mut problems);
+
+        if problems.is_empty() {
+            Ok(settings)
+        } else {
+            Err(SettingsError { problems })
+        }
+    }
+}
+
+fn section<T: DeserializeOwned + Default>(
+    figment: &Figment,
+    key: &str,
+    problems: &mut Vec<String>,
+) -> T {
+    if !figment.contains(key) {
+        return T::default();
+    }
+
+    // Lossy, because environment values arrive as strings: "8080" must still
+    // fill a number and "true" a bool.
+    figment
+        .focus(key)
+        .extract_lossy()
+        .unwrap_or_else(|e: figment::Error| {
+            problems.extend(e.into_iter().map(|e| {
+                let source = e
+                    .metadata
+                    .as_ref()
+                    .map(|metadata| format!(" (from {})", metadata.name))
+                    .unwrap_or_default();
+                if e.path.is_empty() {
+                    format!("{key}: {}{source}", e.kind)
+                } else {
+                    format!("{key}.{}: {}{source}", e.path.join("."), e.kind)
+                }
+            }));
+            T::default()
+        })
+}
+
+/// Maps `APP_SECTION__KEY=value` variables to `("section.key", value)`.
+/// `APP_SECTION__KEY_FILE=path` supplies the value from a file instead, with
+/// its trailing newline stripped. Variables naming no known section, like a
+/// platform's `APP_ENV`, are left alone rather than rejected.
+fn env_overrides(
+    vars: impl Iterator<Item = (String, String)>,
+    problems: &mut Vec<String>,
+) -> Vec<(String, String)> {
+    vars.filter_map(|(name, value)| {
+        let key = name.strip_prefix(ENV_PREFIX)?;
+        let (section, _) = key.split_once("__")?;
+        if !SECTIONS.contains(&section.to_ascii_lowercase().as_str()) {
+            return None;
+        }
+
+        let Some(key) = key.strip_suffix("_FILE") else {
+            return Some((config_key(key), value));
+        };
+
+        match fs::read_to_string(&value) {
+            Ok(contents) => Some((
+                config_key(key),
+                contents.trim_end_matches(['\r', '\n']).to_string(),
+            )),
+            Err(e) => {
+                problems.push(format!("{name}: failed to read `{value}`: {e}"));
+                None
+            }
+        }
+    })
+    .collect()
+}
+
+fn config_key(name: &str) -> String {
+    name.to_ascii_lowercase().replace("__", ".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(toml: &str) -> Result<Settings, SettingsError> {
+        Settings::from_figment(&Figment::from(Toml::string(toml)), Vec::new())
+    }
+
+    const MINIMAL: &str = r#"
+        [database]
+        url = "postgres://localhost/app"
+
+        [jwt]
+        secret = "secret"
+    "#;
+
+    #[test]
+    fn test_defaults_fill_unset_values() {
+        let settings = load(MINIMAL).unwrap();
+
+        assert_eq!(settings.server.port, 3000);
+        assert_eq!(settings.database.max_connections, 5);
+        assert_eq!(settings.jwt.access_token_ttl_minutes, 60);
+        assert_eq!(settings.search.language, "english");
+        assert_eq!(settings.metrics.listen, "127.0.0.1:9090");
+        assert!(!settings.metrics.public);
+    }
+
+    #[test]
+    fn test_later_layers_override_earlier_ones() {
+        let figment = Figment::from(Toml::string(MINIMAL))
+            .merge(Serialized::default("server.port", "8080"))
+            .merge(Serialized::default("jwt.secret", "12345"));
+
+        let settings = Settings::from_figment(&figment, Vec::new()).unwrap();
+
+        assert_eq!(settings.server.port, 8080);
+        assert_eq!(settings.jwt.secret.as_deref(), Some("12345"));
+        assert_eq!(settings.database.url, "postgres://localhost/app");
+    }
+
+    #[test]
+    fn test_reports_every_problem_at_once() {
+        let error = load(
+            r#"
+            [server]
+            port = "not a port"
+
+            [database]
+            max_connections = 0
+
+            [mail]
+            transport = "smtp"
+
+            [metrics]
+            listen = "localhost"
+        "#,
+        )
+        .err()
+        .unwrap();
+
+        let problems = error.problems.join("\n");
+        assert!(problems.contains("server.port"), "{problems}");
+        assert!(problems.contains("database.url"), "{problems}");
+        assert!(problems.contains("database.max_connections"), "{problems}");
+        assert!(problems.contains("jwt.secret"), "{problems}");
+        assert!(problems.contains("mail.smtp_url"), "{problems}");
+        assert!(problems.contains("metrics.listen"), "{problems}");
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        let error = load(&format!("url = \"x\"\n{MINIMAL}\n[server]\nprot = 1\n"))
+            .err()
+            .unwrap();
+
+        assert_eq!(error.problems.len(), 2, "{:?}", error.problems);
+        assert_eq!(error.problems[0], "Unknown setting `url`");
+        assert!(error.problems[1].contains("prot"), "{:?}", error.problems);
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let path = env::temp_dir().join(format!("secret-{}", uuid::Uuid::new_v4()));
+        fs::write(&path, "s3cret\n").unwrap();
+
+        let mut problems = Vec::new();
+        let overrides = env_overrides(
+            [
+                (
+                    "APP_JWT__SECRET_FILE".to_string(),
+                    path.display().to_string(),
+                ),
+                (
+                    "APP_DATABASE__URL_FILE".to_string(),
+                    "/nonexistent/secret".to_string(),
+                ),
+                ("APP_SERVER__PORT".to_string(), "80".to_string()),
+                ("OTHER_FILE".to_string(), "/etc/hosts".to_string()),
+                ("APP_ENV".to_string(), "prod".to_string()),
+                ("APP_VERSION_FILE".to_string(), "/nonexistent".to_string()),
+                ("APP_PLATFORM__REGION".to_string(), "eu".to_string()),
+                ("APP_CONFIG".to_string(), "config.toml".to_string()),
+            ]
+            .into_iter(),
+            &mut problems,
+        );
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(
+            overrides,
+            vec![
+                ("jwt.secret".to_string(), "s3cret".to_string()),
+                ("server.port".to_string(), "80".to_string()),
+            ]
+        );
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("APP_DATABASE__URL_FILE"));
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::storage::{Storage, local::LocalStorage};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Local,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageSettings {
+    pub backend: StorageBackend,
+    /// Root directory of the `local` backend.
+    pub dir: String,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        StorageSettings {
+            backend: StorageBackend::default(),
+            dir: "uploads".to_string(),
+        }
+    }
+}
+
+pub fn load_storage(settings: &StorageSettings) -> Result<Arc<dyn Storage>> {
+    let storage: Arc<dyn Storage> = match settings.backend {
+        StorageBackend::Local => Arc::new(LocalStorage::new(&settings.dir)),
+    };
+
+    Ok(storage)
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::utils::throttle::{
+    LoginThrottle, LoginThrottleConfig, MemoryLoginAttemptStore, ThrottlePolicy,
+};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThrottleSettings {
+    /// Take the client IP from `X-Forwarded-For`; only behind a trusted proxy.
+    pub trust_proxy_headers: bool,
+}
+
+pub fn load_login_throttle(settings: &ThrottleSettings) -> Result<LoginThrottle> {
+    let config = LoginThrottleConfig {
+        account: ThrottlePolicy {
+            free_attempts: 3,
+            base_delay_secs: 1,
+            max_delay_secs: 60,
+            lockout_threshold: 10,
+            lockout_secs: 15 * 60,
+            window_secs: 60 * 60,
+        },
+        // Looser limits per IP, since many users can share one address.
+        ip: ThrottlePolicy {
+            free_attempts: 20,
+            base_delay_secs: 1,
+            max_delay_secs: 60,
+            lockout_threshold: 100,
+            lockout_secs: 15 * 60,
+            window_secs: 60 * 60,
+        },
+        trust_proxy_headers: settings.trust_proxy_headers,
+    };
+
+    Ok(LoginThrottle::new(
+        Arc::new(MemoryLoginAttemptStore::default()),
+        config,
+    ))
+}
@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::{Email, Mailer};
+
+/// Development mailer that prints messages to stdout or drops them into a directory.
+pub struct FileMailer {
+    dir: Option<PathBuf>,
+}
+
+impl FileMailer {
+    pub fn stdout() -> Self {
+        FileMailer { dir: None }
+    }
+
+    pub fn directory(dir: impl Into<PathBuf>) -> Self {
+        FileMailer {
+            dir: Some(dir.into()),
+        }
+    }
+
+    fn render(email: &Email) -> String {
+        format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            email.to, email.subject, email.body
+        )
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        let rendered = Self::render(&email);
+
+        match &self.dir {
+            Some(dir) => {
+                tokio::fs::create_dir_all(dir).await?;
+                let file_name = format!("{}-{}.eml", Utc::now().timestamp(), Uuid::new_v4());
+                tokio::fs::write(dir.join(file_name), rendered).await?;
+            }
+            None => println!("{rendered}"),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_mailer_writes_message_to_directory() {
+        let dir = std::env::temp_dir().join(format!("mailer-test-{}", Uuid::new_v4()));
+        let mailer = FileMailer::directory(&dir);
+
+        mailer
+            .send(Email {
+                to: "user@example.com".to_string(),
+                subject: "Hello".to_string(),
+                body: "Body".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let mut entries = std::fs::read_dir(&dir).unwrap();
+        let path = entries.next().unwrap().unwrap().path();
+        let contents = std::fs::read_to_string(path).unwrap();
+
+        assert!(contents.contains("To: user@example.com"));
+        assert!(contents.contains("Subject: Hello"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
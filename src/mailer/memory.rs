@@ -0,0 +1,29 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{Email, Mailer};
+
+/// Test mailer that keeps every message in memory for later assertions.
+#[derive(Clone, Default)]
+pub struct MemoryMailer {
+    sent: Arc<Mutex<Vec<Email>>>,
+}
+
+impl MemoryMailer {
+    pub fn sent(&self) -> Vec<Email> {
+        self.sent.lock().expect("memory mailer poisoned").clone()
+    }
+}
+
+#[async_trait]
+impl Mailer for MemoryMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        self.sent
+            .lock()
+            .expect("memory mailer poisoned")
+            .push(email);
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub mod file;
+#[cfg(test)]
+pub mod memory;
+pub mod smtp;
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<()>;
+}
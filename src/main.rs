@@ -1,57 +1,224 @@
 use anyhow::Result;
-use axum::{Json, Router, routing::get};
-use serde::Serialize;
+use std::{future::IntoFuture, net::SocketAddr, time::Duration};
+
+use axum::{Router, http::header, response::IntoResponse, routing::get};
+use metrics_exporter_prometheus::PrometheusHandle;
 use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-    config::{db, jwt},
+    config::{account, db, jwt, log, mail, metrics, product, search, settings::Settings, throttle},
+    modules::{
+        product::product_service::ProductService,
+        user::{revoked_token_service::RevokedTokenService, user_controller::jwks_handler},
+    },
     state::AppState,
+    utils::{
+        api_key_cache::ApiKeyCache,
+        revocation::RevocationCache,
+        shutdown::{DrainState, shutdown_signal},
+    },
 };
 
 mod config;
+mod mailer;
 mod middleware;
 mod modules;
 mod state;
+mod storage;
 mod utils;
 
-#[derive(Serialize)]
-struct HealthResponse {
-    status: &'static str,
-}
-
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
 
-    let db = db::establish_connection().await?;
-    let jwt_config = jwt::load_jwt_config()?;
+    let settings = Settings::load()?;
+    log::init_tracing(&settings.log)?;
+    let metrics_handle = settings
+        .metrics
+        .enabled
+        .then(metrics::install_recorder)
+        .transpose()?;
 
-    let state = AppState { db, jwt_config };
+    let db = db::establish_connection(&settings.database).await?;
+    let search_config = search::load_search_config(&settings.search)?;
+    db::prepare_schema(&db, &settings.database, &search_config.language).await?;
+    let jwt_config = jwt::load_jwt_config(&settings.jwt)?;
+    let account_config = account::load_account_config(&settings.account)?;
+    let mailer = mail::load_mailer(&settings.mail)?;
+    let login_throttle = throttle::load_login_throttle(&settings.throttle)?;
+    search::verify_search_language(&db, &search_config).await?;
+    let product_config = product::load_product_config(&settings.product)?;
+    let storage = config::storage::load_storage(&settings.storage)?;
 
-    let app = Router::new()
-        .route(
-            "/health",
-            get(|| async { Json(HealthResponse { status: "ok" }) }),
+    let revoked_tokens = RevocationCache::default();
+    RevokedTokenService::sync_revocations(
+        &db,
+        &revoked_tokens,
+        jwt_config.access_token_ttl_minutes,
+    )
+    .await?;
+    let revocation_sync = RevokedTokenService::spawn_revocation_sync(
+        db.clone(),
+        revoked_tokens.clone(),
+        jwt_config.access_token_ttl_minutes,
+        Duration::from_secs(60),
+    );
+    let trash_purge = ProductService::spawn_trash_purge(
+        db.clone(),
+        storage.clone(),
+        product_config.trash_retention_days,
+        Duration::from_secs(60 * 60),
+    );
+    let pool_sampler = metrics_handle.clone().map(|handle| {
+        utils::metrics::spawn_pool_sampler(
+            db.clone(),
+            handle,
+            Duration::from_secs(settings.metrics.pool_sample_interval_secs),
         )
+    });
+
+    let drain = DrainState::default();
+
+    let state = AppState {
+        db: db.clone(),
+        jwt_config,
+        revoked_tokens,
+        api_keys: ApiKeyCache::default(),
+        account_config,
+        mailer,
+        login_throttle,
+        search_config,
+        product_config,
+        storage,
+        drain: drain.clone(),
+    };
+
+    let mut app = Router::new()
+        .nest("/health", modules::health::health_route::router())
+        .route("/.well-known/jwks.json", get(jwks_handler))
         .nest("/api/users", modules::user::user_route::router())
-        .nest("/api/products", modules::product::product_route::router())
-        .with_state(state);
+        .nest(
+            "/api/products",
+            modules::product::product_route::router(&state.product_config),
+        )
+        .nest(
+            "/api/categories",
+            modules::category::category_route::router(),
+        )
+        .nest("/api/tags", modules::tag::tag_route::router())
+        .layer(axum::middleware::from_fn(middleware::trace_request));
+
+    // Only on the public listener when explicitly opted into, and merged
+    // after the trace layer so scrapes do not count themselves.
+    if let Some(handle) = &metrics_handle
+        && settings.metrics.public
+    {
+        app = app.merge(metrics_router(handle.clone()));
+    }
+    let app = app.with_state(state);
 
-    let listener = match TcpListener::bind("0.0.0.0:3000").await {
+    let bind_address = settings.server.bind_address();
+    let listener = match TcpListener::bind(&bind_address).await {
         Ok(listener) => listener,
         Err(e) => {
-            eprintln!("Failed to bind to port 3000: {e}");
+            tracing::error!("Failed to bind to {bind_address}: {e}");
             return Err(e.into());
         }
     };
 
     let local_addr = listener.local_addr()?;
-    println!("Server running on http://{local_addr}");
+    tracing::info!("Server running on http://{local_addr}");
+
+    // Readiness flips first; the listener keeps accepting until the delay
+    // has given load balancers time to notice. A second signal skips it.
+    let shutdown = CancellationToken::new();
+    let shutdown_delay = Duration::from_secs(settings.server.shutdown_delay_secs);
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown_signal().await;
+            tracing::info!(
+                "Shutdown signal received, reporting draining for {}s before closing the listener",
+                shutdown_delay.as_secs()
+            );
+            drain.start_draining();
+            tokio::select! {
+                _ = tokio::time::sleep(shutdown_delay) => {}
+                _ = shutdown_signal() => {}
+            }
+            tracing::info!("Closing the listener, draining in-flight requests");
+            shutdown.cancel();
+        }
+    });
 
-    if let Err(e) = axum::serve(listener, app).await {
-        eprintln!("Server error: {e}");
-        return Err(e.into());
+    let metrics_server = match &metrics_handle {
+        Some(handle) if !settings.metrics.public => {
+            let listener = TcpListener::bind(&settings.metrics.listen).await?;
+            tracing::info!(
+                "Metrics available on http://{}/metrics",
+                listener.local_addr()?
+            );
+            Some(tokio::spawn(
+                axum::serve(listener, metrics_router(handle.clone()))
+                    .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+                    .into_future(),
+            ))
+        }
+        _ => None,
+    };
+
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+    .into_future();
+
+    let shutdown_timeout = Duration::from_secs(settings.server.shutdown_timeout_secs);
+    let deadline = async {
+        shutdown.cancelled().await;
+        tokio::time::sleep(shutdown_timeout).await;
+    };
+
+    tokio::select! {
+        result = server => {
+            if let Err(e) = result {
+                tracing::error!("Server error: {e}");
+                return Err(e.into());
+            }
+        }
+        _ = deadline => {
+            tracing::warn!(
+                "In-flight requests still running after {}s, shutting down anyway",
+                shutdown_timeout.as_secs()
+            );
+        }
+    }
+
+    revocation_sync.abort();
+    trash_purge.abort();
+    if let Some(pool_sampler) = pool_sampler {
+        pool_sampler.abort();
     }
+    if let Some(metrics_server) = metrics_server {
+        metrics_server.abort();
+    }
+    db.close().await?;
+    tracing::info!("Server stopped");
 
     Ok(())
 }
+
+fn metrics_router<S: Clone + Send + Sync + 'static>(handle: PrometheusHandle) -> Router<S> {
+    Router::new().route(
+        "/metrics",
+        get(move || async move {
+            (
+                [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+                handle.render(),
+            )
+                .into_response()
+        }),
+    )
+}
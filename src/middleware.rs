@@ -1,13 +1,79 @@
+use std::{marker::PhantomData, net::SocketAddr, time::Instant};
+
+use chrono::Utc;
+use tracing::{Instrument, field::Empty};
+
 use crate::{
-    modules::shared::error::AppError,
+    modules::{
+        shared::error::AppError,
+        user::{
+            api_key_entity::{self, ApiScope},
+            api_key_service::{API_KEY_PREFIX, ApiKeyService},
+            user_entity::{self, UserRole},
+        },
+    },
     state::AppState,
-    utils::auth::{Claims, verify_token},
+    utils::{
+        auth::{Claims, verify_token},
+        metrics,
+        request_id::{self, REQUEST_ID_HEADER},
+    },
 };
 use axum::{
-    extract::FromRequestParts,
-    http::{header, request::Parts},
+    extract::{ConnectInfo, FromRequestParts, MatchedPath, Request},
+    http::{HeaderValue, header, request::Parts},
+    middleware::Next,
+    response::Response,
 };
 
+/// Wraps each request in a span carrying its id, method, route template and,
+/// once known, the authenticated `sub`, status and latency. The request id
+/// is echoed in the `X-Request-Id` response header. Also feeds the HTTP
+/// request metrics.
+pub async fn trace_request(request: Request, next: Next) -> Response {
+    let request_id = request_id::from_headers(request.headers());
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", MatchedPath::as_str)
+        .to_string();
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %method,
+        route = %route,
+        sub = Empty,
+        status = Empty,
+        latency_ms = Empty,
+    );
+
+    let started = Instant::now();
+    let mut response = request_id::scope(request_id.clone(), next.run(request))
+        .instrument(span.clone())
+        .await;
+
+    let elapsed = started.elapsed();
+    let status = response.status();
+    span.record("status", status.as_u16());
+    span.record("latency_ms", elapsed.as_millis() as u64);
+    metrics::record_http_request(&method, &route, status, elapsed);
+    span.in_scope(|| {
+        if status.is_server_error() {
+            tracing::error!("request failed");
+        } else {
+            tracing::info!("request completed");
+        }
+    });
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
 pub struct AuthClaims(pub Claims);
 
 impl FromRequestParts<AppState> for AuthClaims {
@@ -27,9 +93,183 @@ impl FromRequestParts<AppState> for AuthClaims {
             .strip_prefix("Bearer ")
             .ok_or_else(|| AppError::Unauthorized("Invalid Authorization scheme".into()))?;
 
+        if token.starts_with(API_KEY_PREFIX) {
+            let (api_key, user) = ApiKeyService::authenticate(&state.db, &state.api_keys, token)
+                .await
+                .map_err(AppError::internal)?
+                .ok_or_else(|| AppError::Unauthorized("Invalid or expired API key".into()))?;
+
+            tracing::Span::current().record("sub", user.id);
+            return Ok(AuthClaims(api_key_claims(
+                &api_key,
+                &user,
+                &state.jwt_config.issuer,
+            )));
+        }
+
         let claims = verify_token(&state.jwt_config, token)
             .map_err(|_| AppError::Unauthorized("Invalid or expired token".into()))?;
 
+        if state.revoked_tokens.is_revoked(&claims.jti)
+            || state
+                .revoked_tokens
+                .is_session_revoked(claims.sub, claims.iat)
+        {
+            return Err(AppError::Unauthorized("Token has been revoked".into()));
+        }
+
+        tracing::Span::current().record("sub", claims.sub);
         Ok(AuthClaims(claims))
     }
 }
+
+fn api_key_claims(
+    api_key: &api_key_entity::Model,
+    user: &user_entity::Model,
+    issuer: &str,
+) -> Claims {
+    let now = Utc::now().timestamp();
+
+    Claims {
+        sub: user.id,
+        jti: format!("api-key:{}", api_key.id),
+        role: user.role,
+        scopes: Some(api_key.api_scopes()),
+        exp: api_key
+            .expires_at
+            .map_or(i64::MAX, |expires_at| expires_at.and_utc().timestamp()),
+        iat: now,
+        iss: issuer.to_string(),
+        aud: None,
+    }
+}
+
+pub trait RoleRequirement {
+    const ROLE: UserRole;
+}
+
+pub struct Admin;
+
+impl RoleRequirement for Admin {
+    const ROLE: UserRole = UserRole::Admin;
+}
+
+/// Requires a user session with role `R`. API keys are rejected outright:
+/// their scopes never cover administration, whatever their owner's role.
+pub struct RequireRole<R: RoleRequirement>(pub Claims, pub PhantomData<R>);
+
+impl<R> FromRequestParts<AppState> for RequireRole<R>
+where
+    R: RoleRequirement + Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let SessionClaims(claims) = SessionClaims::from_request_parts(parts, state).await?;
+
+        if !claims.role.grants(R::ROLE) {
+            return Err(AppError::Forbidden("Insufficient role".into()));
+        }
+
+        Ok(RequireRole(claims, PhantomData))
+    }
+}
+
+pub trait ScopeRequirement {
+    const SCOPE: ApiScope;
+}
+
+pub struct ProductsRead;
+
+impl ScopeRequirement for ProductsRead {
+    const SCOPE: ApiScope = ApiScope::ProductsRead;
+}
+
+pub struct ProductsWrite;
+
+impl ScopeRequirement for ProductsWrite {
+    const SCOPE: ApiScope = ApiScope::ProductsWrite;
+}
+
+/// Accepts JWT sessions unconditionally and API keys carrying the scope `S`.
+pub struct RequireScope<S: ScopeRequirement>(pub Claims, pub PhantomData<S>);
+
+impl<S> FromRequestParts<AppState> for RequireScope<S>
+where
+    S: ScopeRequirement + Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let AuthClaims(claims) = AuthClaims::from_request_parts(parts, state).await?;
+
+        if !claims.has_scope(S::SCOPE) {
+            return Err(AppError::Forbidden(format!(
+                "API key lacks the `{}` scope",
+                S::SCOPE.as_str()
+            )));
+        }
+
+        Ok(RequireScope(claims, PhantomData))
+    }
+}
+
+/// Rejects API keys, for endpoints that manage the account itself.
+pub struct SessionClaims(pub Claims);
+
+impl FromRequestParts<AppState> for SessionClaims {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let AuthClaims(claims) = AuthClaims::from_request_parts(parts, state).await?;
+
+        if claims.is_api_key() {
+            return Err(AppError::Forbidden(
+                "This endpoint requires a user session".into(),
+            ));
+        }
+
+        Ok(SessionClaims(claims))
+    }
+}
+
+pub struct ClientIp(pub Option<String>);
+
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        if state.login_throttle.config.trust_proxy_headers {
+            let forwarded = parts
+                .headers
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(',').next())
+                .map(str::trim)
+                .filter(|ip| !ip.is_empty());
+
+            if let Some(ip) = forwarded {
+                return Ok(ClientIp(Some(ip.to_string())));
+            }
+        }
+
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+        Ok(ClientIp(ip))
+    }
+}
@@ -0,0 +1,41 @@
+use axum::{Json, extract::State, http::StatusCode};
+use validator::Validate;
+
+use super::{
+    category_dto::{CategoryResponse, CreateCategoryPayload},
+    category_service::CategoryService,
+};
+use crate::{
+    middleware::{Admin, ProductsRead, RequireRole, RequireScope},
+    modules::shared::error::AppError,
+    state::AppState,
+};
+
+pub async fn find_categories_handler(
+    State(state): State<AppState>,
+    _scope: RequireScope<ProductsRead>,
+) -> Result<(StatusCode, Json<Vec<CategoryResponse>>), AppError> {
+    let categories = CategoryService::find_categories(&state.db)
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(categories.into_iter().map(Into::into).collect()),
+    ))
+}
+
+pub async fn create_category_handler(
+    State(state): State<AppState>,
+    _role: RequireRole<Admin>,
+    Json(payload): Json<CreateCategoryPayload>,
+) -> Result<(StatusCode, Json<CategoryResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let category = CategoryService::create_category(&state.db, payload.name.trim().to_string())
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::Conflict("Category already exists".to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(category.into())))
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::category_entity;
+
+#[derive(Debug, Serialize)]
+pub struct CategoryResponse {
+    pub id: i32,
+    pub name: String,
+}
+
+impl From<category_entity::Model> for CategoryResponse {
+    fn from(category: category_entity::Model) -> Self {
+        CategoryResponse {
+            id: category.id,
+            name: category.name,
+        }
+    }
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct CreateCategoryPayload {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+}
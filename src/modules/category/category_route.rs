@@ -0,0 +1,11 @@
+use axum::{Router, routing::get};
+
+use super::category_controller::{create_category_handler, find_categories_handler};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route(
+        "/",
+        get(find_categories_handler).post(create_category_handler),
+    )
+}
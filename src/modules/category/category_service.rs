@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, SqlErr,
+};
+
+use super::{category_entity, product_category_entity};
+use crate::modules::product::product_entity;
+
+#[derive(Clone)]
+pub struct CategoryService;
+
+impl CategoryService {
+    pub async fn find_categories(db: &DatabaseConnection) -> Result<Vec<category_entity::Model>> {
+        let categories = category_entity::Entity::find()
+            .order_by_asc(category_entity::Column::Name)
+            .all(db)
+            .await?;
+        Ok(categories)
+    }
+
+    /// Returns `None` if a category with that name already exists.
+    pub async fn create_category(
+        db: &DatabaseConnection,
+        name: String,
+    ) -> Result<Option<category_entity::Model>> {
+        let category = category_entity::ActiveModel {
+            name: Set(name),
+            ..Default::default()
+        };
+
+        match category.insert(db).await {
+            Ok(category) => Ok(Some(category)),
+            Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The ids in `ids` that do not name a category.
+    pub async fn find_missing_ids(db: &DatabaseConnection, ids: &[i32]) -> Result<Vec<i32>> {
+        let existing: HashSet<i32> = category_entity::Entity::find()
+            .select_only()
+            .column(category_entity::Column::Id)
+            .filter(category_entity::Column::Id.is_in(ids.iter().copied()))
+            .into_tuple::<i32>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(ids
+            .iter()
+            .copied()
+            .filter(|id| !existing.contains(id))
+            .collect())
+    }
+
+    pub async fn find_product_categories<C: ConnectionTrait>(
+        db: &C,
+        product: &product_entity::Model,
+    ) -> Result<Vec<category_entity::Model>> {
+        let categories = product
+            .find_related(category_entity::Entity)
+            .order_by_asc(category_entity::Column::Name)
+            .all(db)
+            .await?;
+        Ok(categories)
+    }
+
+    /// Replaces the product's categories with `category_ids`.
+    pub async fn set_product_categories<C: ConnectionTrait>(
+        db: &C,
+        product_id: i32,
+        category_ids: &[i32],
+    ) -> Result<()> {
+        product_category_entity::Entity::delete_many()
+            .filter(product_category_entity::Column::ProductId.eq(product_id))
+            .exec(db)
+            .await?;
+
+        if category_ids.is_empty() {
+            return Ok(());
+        }
+
+        let unique_ids: HashSet<i32> = category_ids.iter().copied().collect();
+        let links =
+            unique_ids
+                .into_iter()
+                .map(|category_id| product_category_entity::ActiveModel {
+                    product_id: Set(product_id),
+                    category_id: Set(category_id),
+                });
+
+        product_category_entity::Entity::insert_many(links)
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
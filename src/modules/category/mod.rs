@@ -0,0 +1,6 @@
+pub mod category_controller;
+pub mod category_dto;
+pub mod category_entity;
+pub mod category_route;
+pub mod category_service;
+pub mod product_category_entity;
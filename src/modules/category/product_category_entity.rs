@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::category_entity;
+use crate::modules::product::product_entity;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "product_categories")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub product_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub category_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "product_entity::Entity",
+        from = "Column::ProductId",
+        to = "product_entity::Column::Id",
+        on_delete = "Cascade",
+        on_update = "Cascade"
+    )]
+    Product,
+    #[sea_orm(
+        belongs_to = "category_entity::Entity",
+        from = "Column::CategoryId",
+        to = "category_entity::Column::Id",
+        on_delete = "Cascade",
+        on_update = "Cascade"
+    )]
+    Category,
+}
+
+impl Related<product_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Product.def()
+    }
+}
+
+impl Related<category_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Category.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
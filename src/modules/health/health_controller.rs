@@ -0,0 +1,131 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, bail};
+use axum::{Json, extract::State, http::StatusCode};
+
+use super::{
+    health_dto::{CheckResult, CheckStatus, LivenessResponse, ReadinessResponse},
+    health_service::HealthService,
+};
+use crate::state::AppState;
+
+/// Each readiness check gets this long before it counts as failed, so a hung
+/// database cannot stall the probe past the orchestrator's own timeout.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The process is up and serving; says nothing about its dependencies.
+pub async fn live_handler() -> Json<LivenessResponse> {
+    Json(LivenessResponse { status: "ok" })
+}
+
+pub async fn ready_handler(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    if state.drain.is_draining() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ReadinessResponse {
+                status: "draining",
+                checks: BTreeMap::new(),
+            }),
+        );
+    }
+
+    let (database, migrations) = tokio::join!(
+        run_check(
+            "database",
+            true,
+            CHECK_TIMEOUT,
+            HealthService::ping_database(&state.db)
+        ),
+        run_check("migrations", true, CHECK_TIMEOUT, async {
+            let pending = HealthService::pending_migrations(&state.db).await?;
+            if !pending.is_empty() {
+                bail!("Pending migrations: {}", pending.join(", "));
+            }
+            Ok(())
+        }),
+    );
+    let checks = BTreeMap::from([("database", database), ("migrations", migrations)]);
+
+    let ready = checks
+        .values()
+        .all(|check| !check.required || check.status == CheckStatus::Ok);
+    if ready {
+        (
+            StatusCode::OK,
+            Json(ReadinessResponse {
+                status: "ready",
+                checks,
+            }),
+        )
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ReadinessResponse {
+                status: "not_ready",
+                checks,
+            }),
+        )
+    }
+}
+
+/// Failure details are logged rather than returned, since the probe is
+/// unauthenticated.
+async fn run_check(
+    name: &'static str,
+    required: bool,
+    timeout: Duration,
+    check: impl Future<Output = Result<()>>,
+) -> CheckResult {
+    let started = Instant::now();
+    let error = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(format!("{e:#}")),
+        Err(_) => Some(format!("Timed out after {}ms", timeout.as_millis())),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let status = match error {
+        None => CheckStatus::Ok,
+        Some(error) => {
+            tracing::warn!(check = name, error, latency_ms, "readiness check failed");
+            CheckStatus::Failed
+        }
+    };
+
+    CheckResult {
+        status,
+        required,
+        latency_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_check_reports_errors_and_timeouts() {
+        let ok = run_check("ok", true, CHECK_TIMEOUT, async { Ok(()) }).await;
+        assert_eq!(ok.status, CheckStatus::Ok);
+
+        let failed = run_check("failed", true, CHECK_TIMEOUT, async {
+            bail!("connection refused")
+        })
+        .await;
+        assert_eq!(failed.status, CheckStatus::Failed);
+
+        let timed_out = run_check(
+            "timed_out",
+            false,
+            Duration::from_millis(10),
+            std::future::pending::<Result<()>>(),
+        )
+        .await;
+        assert_eq!(timed_out.status, CheckStatus::Failed);
+        assert!(timed_out.latency_ms >= 10);
+        assert!(!timed_out.required);
+    }
+}
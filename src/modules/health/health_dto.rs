@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct LivenessResponse {
+    pub status: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub status: CheckStatus,
+    /// Whether a failure makes the instance not ready.
+    pub required: bool,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    /// `ready`, `not_ready`, or `draining` during shutdown.
+    pub status: &'static str,
+    pub checks: BTreeMap<&'static str, CheckResult>,
+}
@@ -0,0 +1,12 @@
+use axum::{Router, routing::get};
+
+use super::health_controller::{live_handler, ready_handler};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        // Plain `/health` is kept for existing monitors and reports readiness.
+        .route("/", get(ready_handler))
+        .route("/live", get(live_handler))
+        .route("/ready", get(ready_handler))
+}
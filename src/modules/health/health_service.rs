@@ -0,0 +1,55 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+#[derive(Clone)]
+pub struct HealthService;
+
+impl HealthService {
+    pub async fn ping_database(db: &DatabaseConnection) -> Result<()> {
+        db.ping().await?;
+        Ok(())
+    }
+
+    /// Names of the migrations in `migration::Migrator` not yet applied to
+    /// the database, oldest first. Only reads `seaql_migrations`, unlike
+    /// `MigratorTrait::get_pending_migrations`, which creates the table
+    /// first and so would run DDL. A database without the table has every
+    /// migration pending.
+    pub async fn pending_migrations(db: &impl ConnectionTrait) -> Result<Vec<String>> {
+        let installed = db
+            .query_one(Statement::from_string(
+                DbBackend::Postgres,
+                "SELECT to_regclass('seaql_migrations') IS NOT NULL AS installed",
+            ))
+            .await?
+            .map(|row| row.try_get::<bool>("", "installed"))
+            .transpose()?
+            .unwrap_or(false);
+        if !installed {
+            return Ok(migration_names().collect());
+        }
+
+        let applied: HashSet<String> = db
+            .query_all(Statement::from_string(
+                DbBackend::Postgres,
+                "SELECT version FROM seaql_migrations",
+            ))
+            .await?
+            .iter()
+            .map(|row| row.try_get("", "version"))
+            .collect::<Result<_, _>>()?;
+
+        Ok(migration_names()
+            .filter(|name| !applied.contains(name))
+            .collect())
+    }
+}
+
+fn migration_names() -> impl Iterator<Item = String> {
+    Migrator::migrations()
+        .into_iter()
+        .map(|migration| migration.name().to_string())
+}
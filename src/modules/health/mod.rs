@@ -0,0 +1,4 @@
+pub mod health_controller;
+pub mod health_dto;
+pub mod health_route;
+pub mod health_service;
@@ -1,3 +1,6 @@
+pub mod category;
+pub mod health;
 pub mod product;
 pub mod shared;
+pub mod tag;
 pub mod user;
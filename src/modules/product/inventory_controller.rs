@@ -0,0 +1,134 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use validator::Validate;
+
+use super::{
+    inventory_service::{InventoryService, ReleaseOutcome, StockChange},
+    product_controller::find_product_for_change,
+    product_dto::{
+        AdjustStockPayload, InventoryMovementResponse, ReleaseStockPayload, ReserveStockPayload,
+    },
+};
+use crate::{
+    middleware::{ProductsRead, ProductsWrite, RequireScope},
+    modules::{
+        shared::{
+            error::AppError,
+            pagination::{Page, PageQuery, PageRequest},
+        },
+        user::user_entity::UserRole,
+    },
+    state::AppState,
+};
+
+pub async fn find_movements_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsRead>,
+    Path(product_id): Path<i32>,
+    Query(query): Query<PageQuery>,
+) -> Result<(StatusCode, Json<Page<InventoryMovementResponse>>), AppError> {
+    let page = PageRequest::try_from(query)?;
+
+    find_product_for_change(&state, &claims, product_id).await?;
+
+    let movements = InventoryService::find_movements_page(&state.db, product_id, page)
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok((StatusCode::OK, Json(movements.map(Into::into))))
+}
+
+pub async fn adjust_stock_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
+    Path(product_id): Path<i32>,
+    Json(payload): Json<AdjustStockPayload>,
+) -> Result<(StatusCode, Json<InventoryMovementResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    find_product_for_change(&state, &claims, product_id).await?;
+
+    let change = InventoryService::adjust_stock(
+        &state.db,
+        product_id,
+        claims.sub,
+        payload.delta,
+        payload.reason,
+        payload.note,
+    )
+    .await
+    .map_err(AppError::internal)?;
+
+    Ok((StatusCode::CREATED, Json(applied_movement(change)?)))
+}
+
+/// Any caller allowed to write products may reserve stock, e.g. a checkout
+/// holding items while payment completes.
+pub async fn reserve_stock_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
+    Path(product_id): Path<i32>,
+    Json(payload): Json<ReserveStockPayload>,
+) -> Result<(StatusCode, Json<InventoryMovementResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let change =
+        InventoryService::reserve_stock(&state.db, product_id, claims.sub, payload.quantity)
+            .await
+            .map_err(AppError::internal)?;
+
+    Ok((StatusCode::CREATED, Json(applied_movement(change)?)))
+}
+
+pub async fn release_stock_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
+    Path(product_id): Path<i32>,
+    Json(payload): Json<ReleaseStockPayload>,
+) -> Result<(StatusCode, Json<InventoryMovementResponse>), AppError> {
+    let reservation =
+        InventoryService::find_reservation(&state.db, product_id, payload.reservation_id)
+            .await
+            .map_err(AppError::internal)?
+            .ok_or_else(|| AppError::NotFound("Reservation not found".to_string()))?;
+
+    if reservation.actor_id != Some(claims.sub) && !claims.role.grants(UserRole::Admin) {
+        return Err(AppError::Forbidden(
+            "Only the reserving user can release this reservation".to_string(),
+        ));
+    }
+
+    let outcome = InventoryService::release_reservation(&state.db, &reservation, claims.sub)
+        .await
+        .map_err(AppError::internal)?;
+
+    match outcome {
+        ReleaseOutcome::Released(movement) => Ok((StatusCode::CREATED, Json(movement.into()))),
+        ReleaseOutcome::ProductNotFound => Err(AppError::NotFound("Product not found".to_string())),
+        ReleaseOutcome::AlreadyReleased => Err(AppError::Conflict(
+            "Reservation has already been released".to_string(),
+        )),
+        ReleaseOutcome::StockLimitExceeded { available } => Err(stock_limit_exceeded(available)),
+    }
+}
+
+fn applied_movement(change: StockChange) -> Result<InventoryMovementResponse, AppError> {
+    match change {
+        StockChange::Applied(movement) => Ok(movement.into()),
+        StockChange::ProductNotFound => Err(AppError::NotFound("Product not found".to_string())),
+        StockChange::InsufficientStock { available } => Err(AppError::Conflict(format!(
+            "Insufficient stock: {available} available"
+        ))),
+        StockChange::StockLimitExceeded { available } => Err(stock_limit_exceeded(available)),
+    }
+}
+
+fn stock_limit_exceeded(available: i32) -> AppError {
+    AppError::Conflict(format!(
+        "Stock limit exceeded: {available} in stock, at most {} allowed",
+        i32::MAX
+    ))
+}
@@ -0,0 +1,79 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::product_entity;
+use crate::modules::user::user_entity;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "inventory_movements")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub product_id: i32,
+    pub actor_id: Option<i32>,
+    pub reason: InventoryReason,
+    pub delta: i32,
+    pub stock_after: i32,
+    /// Set on releases: the reservation being undone.
+    #[sea_orm(unique)]
+    pub reservation_id: Option<i32>,
+    pub note: Option<String>,
+    pub created_at: DateTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+#[serde(rename_all = "snake_case")]
+pub enum InventoryReason {
+    #[sea_orm(string_value = "restock")]
+    Restock,
+    #[sea_orm(string_value = "correction")]
+    Correction,
+    #[sea_orm(string_value = "damaged")]
+    Damaged,
+    #[sea_orm(string_value = "returned")]
+    Returned,
+    #[sea_orm(string_value = "reservation")]
+    Reservation,
+    #[sea_orm(string_value = "release")]
+    Release,
+}
+
+impl InventoryReason {
+    /// Reservations and releases are only recorded by the reserve/release
+    /// endpoints, never by manual adjustments.
+    pub fn is_manual(self) -> bool {
+        !matches!(
+            self,
+            InventoryReason::Reservation | InventoryReason::Release
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "product_entity::Entity",
+        from = "Column::ProductId",
+        to = "product_entity::Column::Id",
+        on_delete = "Cascade",
+        on_update = "Cascade"
+    )]
+    Product,
+    #[sea_orm(
+        belongs_to = "user_entity::Entity",
+        from = "Column::ActorId",
+        to = "user_entity::Column::Id",
+        on_delete = "SetNull",
+        on_update = "Cascade"
+    )]
+    Actor,
+}
+
+impl Related<product_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Product.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
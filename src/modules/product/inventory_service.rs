@@ -0,0 +1,455 @@
+use anyhow::Result;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    QueryFilter, Set, SqlErr, TransactionTrait, sea_query::Expr,
+};
+
+use super::{
+    inventory_movement_entity::{self, InventoryReason},
+    product_entity,
+};
+use crate::modules::shared::pagination::{Cursor, Page, PageRequest};
+
+#[derive(Debug)]
+pub enum StockChange {
+    Applied(inventory_movement_entity::Model),
+    ProductNotFound,
+    InsufficientStock {
+        available: i32,
+    },
+    /// A restock would push stock past what the column can hold.
+    StockLimitExceeded {
+        available: i32,
+    },
+}
+
+#[derive(Debug)]
+pub enum ReleaseOutcome {
+    Released(inventory_movement_entity::Model),
+    ProductNotFound,
+    AlreadyReleased,
+    StockLimitExceeded { available: i32 },
+}
+
+#[derive(Clone)]
+pub struct InventoryService;
+
+impl InventoryService {
+    pub async fn adjust_stock(
+        db: &DatabaseConnection,
+        product_id: i32,
+        actor_id: i32,
+        delta: i32,
+        reason: InventoryReason,
+        note: Option<String>,
+    ) -> Result<StockChange> {
+        let txn = db.begin().await?;
+        let change = Self::record_change(&txn, product_id, actor_id, delta, reason, note).await?;
+        txn.commit().await?;
+        Ok(change)
+    }
+
+    /// Takes `quantity` units out of stock. The returned movement's id is
+    /// the reservation id to pass to `release_reservation`.
+    pub async fn reserve_stock(
+        db: &DatabaseConnection,
+        product_id: i32,
+        actor_id: i32,
+        quantity: i32,
+    ) -> Result<StockChange> {
+        Self::adjust_stock(
+            db,
+            product_id,
+            actor_id,
+            -quantity,
+            InventoryReason::Reservation,
+            None,
+        )
+        .await
+    }
+
+    /// Puts a reservation's units back. Each reservation can be released once;
+    /// the unique `reservation_id` column settles concurrent attempts.
+    pub async fn release_reservation(
+        db: &DatabaseConnection,
+        reservation: &inventory_movement_entity::Model,
+        actor_id: i32,
+    ) -> Result<ReleaseOutcome> {
+        let txn = db.begin().await?;
+
+        let Some(stock_after) =
+            Self::apply_delta(&txn, reservation.product_id, -reservation.delta).await?
+        else {
+            return Ok(
+                match Self::rejected_change(&txn, reservation.product_id, -reservation.delta)
+                    .await?
+                {
+                    StockChange::StockLimitExceeded { available } => {
+                        ReleaseOutcome::StockLimitExceeded { available }
+                    }
+                    _ => ReleaseOutcome::ProductNotFound,
+                },
+            );
+        };
+
+        let movement = inventory_movement_entity::ActiveModel {
+            product_id: Set(reservation.product_id),
+            actor_id: Set(Some(actor_id)),
+            reason: Set(InventoryReason::Release),
+            delta: Set(-reservation.delta),
+            stock_after: Set(stock_after),
+            reservation_id: Set(Some(reservation.id)),
+            ..Default::default()
+        };
+
+        match movement.insert(&txn).await {
+            Ok(movement) => {
+                txn.commit().await?;
+                Ok(ReleaseOutcome::Released(movement))
+            }
+            Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+                Ok(ReleaseOutcome::AlreadyReleased)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn find_reservation(
+        db: &DatabaseConnection,
+        product_id: i32,
+        reservation_id: i32,
+    ) -> Result<Option<inventory_movement_entity::Model>> {
+        let reservation = inventory_movement_entity::Entity::find_by_id(reservation_id)
+            .filter(inventory_movement_entity::Column::ProductId.eq(product_id))
+            .filter(inventory_movement_entity::Column::Reason.eq(InventoryReason::Reservation))
+            .one(db)
+            .await?;
+        Ok(reservation)
+    }
+
+    pub async fn find_movements_page(
+        db: &DatabaseConnection,
+        product_id: i32,
+        page: PageRequest,
+    ) -> Result<Page<inventory_movement_entity::Model>> {
+        let movements = page
+            .apply(
+                inventory_movement_entity::Entity::find()
+                    .filter(inventory_movement_entity::Column::ProductId.eq(product_id)),
+                inventory_movement_entity::Column::CreatedAt,
+                inventory_movement_entity::Column::Id,
+            )
+            .all(db)
+            .await?;
+
+        Ok(page.into_page(movements, |movement| Cursor {
+            created_at: movement.created_at,
+            id: movement.id,
+        }))
+    }
+
+    async fn record_change(
+        txn: &DatabaseTransaction,
+        product_id: i32,
+        actor_id: i32,
+        delta: i32,
+        reason: InventoryReason,
+        note: Option<String>,
+    ) -> Result<StockChange> {
+        let Some(stock_after) = Self::apply_delta(txn, product_id, delta).await? else {
+            return Self::rejected_change(txn, product_id, delta).await;
+        };
+
+        let movement = inventory_movement_entity::ActiveModel {
+            product_id: Set(product_id),
+            actor_id: Set(Some(actor_id)),
+            reason: Set(reason),
+            delta: Set(delta),
+            stock_after: Set(stock_after),
+            note: Set(note),
+            ..Default::default()
+        };
+
+        Ok(StockChange::Applied(movement.insert(txn).await?))
+    }
+
+    /// Explains why `apply_delta` refused `delta`.
+    async fn rejected_change(
+        txn: &DatabaseTransaction,
+        product_id: i32,
+        delta: i32,
+    ) -> Result<StockChange> {
+        let product = product_entity::Entity::find_by_id(product_id)
+            .filter(product_entity::Column::DeletedAt.is_null())
+            .one(txn)
+            .await?;
+
+        Ok(match product {
+            Some(product) if delta > 0 => StockChange::StockLimitExceeded {
+                available: product.stock,
+            },
+            Some(product) => StockChange::InsufficientStock {
+                available: product.stock,
+            },
+            None => StockChange::ProductNotFound,
+        })
+    }
+
+    /// Moves stock by `delta` in a single conditional UPDATE, so concurrent
+    /// callers serialize on the row lock and none can push stock below zero
+    /// or past `i32::MAX`. Returns the new stock, or `None` if the product is
+    /// missing or the change would oversell or overflow.
+    async fn apply_delta(
+        txn: &DatabaseTransaction,
+        product_id: i32,
+        delta: i32,
+    ) -> Result<Option<i32>> {
+        let updated = product_entity::Entity::update_many()
+            .col_expr(
+                product_entity::Column::Stock,
+                Expr::col(product_entity::Column::Stock).add(delta),
+            )
+            .filter(product_entity::Column::Id.eq(product_id))
+            .filter(product_entity::Column::DeletedAt.is_null())
+            .filter(if delta < 0 {
+                product_entity::Column::Stock.gte(-delta)
+            } else {
+                product_entity::Column::Stock.lte(i32::MAX - delta)
+            })
+            .exec_with_returning(txn)
+            .await?;
+
+        Ok(updated.first().map(|product| product.stock))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, sync::Arc};
+
+    use rust_decimal::Decimal;
+    use sea_orm::Database;
+    use tokio::sync::Barrier;
+
+    use super::*;
+    use crate::modules::user::user_entity;
+
+    const CONCURRENT_REQUESTS: usize = 64;
+
+    /// These tests need a migrated Postgres database, e.g.
+    /// `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`.
+    async fn connect() -> DatabaseConnection {
+        let url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        Database::connect(url).await.unwrap()
+    }
+
+    async fn create_product(db: &DatabaseConnection, stock: i32) -> (i32, i32) {
+        let user = user_entity::ActiveModel {
+            email: Set(format!(
+                "inventory-{}@example.com",
+                crate::utils::token::generate_opaque_token()
+            )),
+            password: Set("unused".to_string()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+
+        let product = product_entity::ActiveModel {
+            owner_id: Set(user.id),
+            title: Set("Inventory test".to_string()),
+            price: Set(Decimal::ONE),
+            stock: Set(stock),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+
+        (user.id, product.id)
+    }
+
+    async fn cleanup(db: &DatabaseConnection, user_id: i32) {
+        user_entity::Entity::delete_by_id(user_id)
+            .exec(db)
+            .await
+            .unwrap();
+    }
+
+    async fn current_stock(db: &DatabaseConnection, product_id: i32) -> i32 {
+        product_entity::Entity::find_by_id(product_id)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap()
+            .stock
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn test_concurrent_reservations_never_oversell() {
+        let db = connect().await;
+        let (user_id, product_id) = create_product(&db, 25).await;
+        let barrier = Arc::new(Barrier::new(CONCURRENT_REQUESTS));
+
+        let handles: Vec<_> = (0..CONCURRENT_REQUESTS)
+            .map(|_| {
+                let db = db.clone();
+                let barrier = barrier.clone();
+                tokio::spawn(async move {
+                    barrier.wait().await;
+                    InventoryService::reserve_stock(&db, product_id, user_id, 1)
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        let mut applied = Vec::new();
+        for handle in handles {
+            match handle.await.unwrap() {
+                StockChange::Applied(movement) => applied.push(movement.stock_after),
+                StockChange::InsufficientStock { available } => assert_eq!(available, 0),
+                other => panic!("unexpected outcome: {other:?}"),
+            }
+        }
+
+        // Every successful reservation saw a distinct stock level.
+        applied.sort_unstable();
+        assert_eq!(applied, (0..25).collect::<Vec<_>>());
+        assert_eq!(current_stock(&db, product_id).await, 0);
+
+        cleanup(&db, user_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn test_restock_past_stock_limit_is_rejected() {
+        let db = connect().await;
+        let (user_id, product_id) = create_product(&db, i32::MAX - 5).await;
+
+        let change = InventoryService::adjust_stock(
+            &db,
+            product_id,
+            user_id,
+            6,
+            InventoryReason::Restock,
+            None,
+        )
+        .await
+        .unwrap();
+        match change {
+            StockChange::StockLimitExceeded { available } => assert_eq!(available, i32::MAX - 5),
+            other => panic!("unexpected outcome: {other:?}"),
+        }
+
+        let change = InventoryService::adjust_stock(
+            &db,
+            product_id,
+            user_id,
+            5,
+            InventoryReason::Restock,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(change, StockChange::Applied(_)));
+        assert_eq!(current_stock(&db, product_id).await, i32::MAX);
+
+        cleanup(&db, user_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn test_concurrent_mixed_movements_keep_ledger_consistent() {
+        let db = connect().await;
+        let (user_id, product_id) = create_product(&db, 10).await;
+        let barrier = Arc::new(Barrier::new(CONCURRENT_REQUESTS));
+
+        let handles: Vec<_> = (0..CONCURRENT_REQUESTS)
+            .map(|i| {
+                let db = db.clone();
+                let barrier = barrier.clone();
+                tokio::spawn(async move {
+                    barrier.wait().await;
+                    let (delta, reason) = if i % 4 == 0 {
+                        (3, InventoryReason::Restock)
+                    } else {
+                        (-2, InventoryReason::Reservation)
+                    };
+                    InventoryService::adjust_stock(&db, product_id, user_id, delta, reason, None)
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        let mut net = 0;
+        for handle in handles {
+            if let StockChange::Applied(movement) = handle.await.unwrap() {
+                assert!(movement.stock_after >= 0);
+                net += movement.delta;
+            }
+        }
+
+        let ledger: i32 = inventory_movement_entity::Entity::find()
+            .filter(inventory_movement_entity::Column::ProductId.eq(product_id))
+            .all(&db)
+            .await
+            .unwrap()
+            .iter()
+            .map(|movement| movement.delta)
+            .sum();
+
+        assert_eq!(ledger, net);
+        assert_eq!(current_stock(&db, product_id).await, 10 + net);
+
+        cleanup(&db, user_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn test_reservation_is_released_exactly_once() {
+        let db = connect().await;
+        let (user_id, product_id) = create_product(&db, 5).await;
+
+        let StockChange::Applied(reservation) =
+            InventoryService::reserve_stock(&db, product_id, user_id, 4)
+                .await
+                .unwrap()
+        else {
+            panic!("reservation failed");
+        };
+        assert_eq!(current_stock(&db, product_id).await, 1);
+
+        let barrier = Arc::new(Barrier::new(CONCURRENT_REQUESTS));
+        let handles: Vec<_> = (0..CONCURRENT_REQUESTS)
+            .map(|_| {
+                let db = db.clone();
+                let barrier = barrier.clone();
+                let reservation = reservation.clone();
+                tokio::spawn(async move {
+                    barrier.wait().await;
+                    InventoryService::release_reservation(&db, &reservation, user_id)
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        let mut released = 0;
+        for handle in handles {
+            match handle.await.unwrap() {
+                ReleaseOutcome::Released(_) => released += 1,
+                ReleaseOutcome::AlreadyReleased => {}
+                other => panic!("unexpected outcome: {other:?}"),
+            }
+        }
+
+        assert_eq!(released, 1);
+        assert_eq!(current_stock(&db, product_id).await, 5);
+
+        cleanup(&db, user_id).await;
+    }
+}
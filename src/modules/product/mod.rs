@@ -1,5 +1,16 @@
+pub mod inventory_controller;
+pub mod inventory_movement_entity;
+pub mod inventory_service;
 pub mod product_controller;
 pub mod product_dto;
 pub mod product_entity;
+pub mod product_image_controller;
+pub mod product_image_entity;
+pub mod product_image_service;
+pub mod product_revision_controller;
+pub mod product_revision_entity;
+pub mod product_revision_service;
 pub mod product_route;
 pub mod product_service;
+pub mod product_transfer;
+pub mod product_transfer_controller;
@@ -1,89 +1,327 @@
-use axum::{Json, extract::State, http::StatusCode};
-use rust_decimal::{Decimal, prelude::FromPrimitive};
-use validator::{Validate, ValidationError, ValidationErrors};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use validator::Validate;
 
-use super::product_service::ProductService;
+use super::{
+    product_entity,
+    product_service::{NewProduct, ProductChanges, ProductService},
+};
 use crate::{
-    middleware::AuthClaims,
+    middleware::{ProductsRead, ProductsWrite, RequireScope},
     modules::{
-        product::product_dto::{BaseProductResponse, CreateProductPayload, GetProductsResponse},
-        shared::error::AppError,
-        user::user_dto::GetUsersResponse,
+        category::category_service::CategoryService,
+        product::product_dto::{
+            BaseProductResponse, CreateProductPayload, GetProductsResponse, ProductDetailResponse,
+            ProductListQuery, ProductSearchQuery, ProductSearchResponse, TrashedProductResponse,
+            UpdateProductPayload, price_to_decimal,
+        },
+        shared::{
+            error::AppError,
+            pagination::{Page, PageQuery, PageRequest},
+        },
+        tag::tag_service::{TagService, normalize_tag_names},
+        user::{user_dto::GetUsersResponse, user_entity::UserRole, user_service::UserService},
     },
     state::AppState,
+    utils::auth::Claims,
 };
 
 pub async fn find_all_products_handler(
     State(state): State<AppState>,
-    AuthClaims(_claims): AuthClaims,
-) -> Result<(StatusCode, Json<Vec<GetProductsResponse>>), AppError> {
-    let products = ProductService::find_all_products_with_owner(&state.db)
-        .await
-        .map_err(|e| AppError::internal(e))?;
-
-    let response: Vec<GetProductsResponse> = products
-        .into_iter()
-        .map(|(product, owner)| GetProductsResponse {
-            product: BaseProductResponse {
-                id: product.id,
-                owner_id: product.owner_id,
-                title: product.title,
-                content: product.content,
-                price: product.price,
-                created_at: product.created_at.to_string(),
-                updated_at: product.updated_at.to_string(),
-            },
-            owner: owner.map(|user| GetUsersResponse {
-                id: user.id,
-                email: user.email,
-                name: user.name,
-            }),
-        })
-        .collect();
+    _scope: RequireScope<ProductsRead>,
+    query: ProductListQuery,
+) -> Result<(StatusCode, Json<Page<GetProductsResponse>>), AppError> {
+    let products = ProductService::find_products_with_owner_page(
+        &state.db,
+        &query.filter,
+        &query.sort,
+        query.page,
+    )
+    .await
+    .map_err(AppError::internal)?;
+
+    let response = products.map(|(product, owner)| GetProductsResponse {
+        product: product.into(),
+        owner: owner.map(|user| GetUsersResponse {
+            id: user.id,
+            email: user.email,
+            name: user.name,
+            role: user.role,
+        }),
+    });
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+pub async fn search_products_handler(
+    State(state): State<AppState>,
+    _scope: RequireScope<ProductsRead>,
+    Query(query): Query<ProductSearchQuery>,
+) -> Result<(StatusCode, Json<Page<ProductSearchResponse>>), AppError> {
+    query.validate().map_err(AppError::validation)?;
+
+    let page = PageRequest::try_from(query.page_query())?;
+
+    let hits = ProductService::search_products(
+        &state.db,
+        &state.search_config.language,
+        query.q.trim(),
+        page,
+    )
+    .await
+    .map_err(AppError::internal)?;
+
+    let response = hits.map(|hit| ProductSearchResponse {
+        product: BaseProductResponse {
+            id: hit.id,
+            owner_id: hit.owner_id,
+            title: hit.title,
+            content: hit.content,
+            price: hit.price,
+            stock: hit.stock,
+            created_at: hit.created_at.to_string(),
+            updated_at: hit.updated_at.to_string(),
+        },
+        rank: hit.rank,
+        title_highlight: hit.title_highlight,
+        snippet: hit.snippet,
+    });
 
     Ok((StatusCode::OK, Json(response)))
 }
 
+pub async fn find_product_handler(
+    State(state): State<AppState>,
+    _scope: RequireScope<ProductsRead>,
+    Path(product_id): Path<i32>,
+) -> Result<(StatusCode, Json<ProductDetailResponse>), AppError> {
+    let product = ProductService::find_product_by_id(&state.db, product_id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+    Ok((StatusCode::OK, Json(product_detail(&state, product).await?)))
+}
+
 pub async fn create_product_handler(
     State(state): State<AppState>,
-    AuthClaims(claims): AuthClaims,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
     Json(payload): Json<CreateProductPayload>,
-) -> Result<(StatusCode, Json<BaseProductResponse>), AppError> {
+) -> Result<(StatusCode, Json<ProductDetailResponse>), AppError> {
     payload.validate().map_err(AppError::validation)?;
 
-    let mut validate_price = ValidationErrors::new();
+    ensure_can_create_products(&state, &claims).await?;
+
+    let price = price_to_decimal(payload.price).map_err(AppError::validation)?;
+    let category_ids = payload.category_ids.unwrap_or_default();
+    ensure_categories_exist(&state, &category_ids).await?;
+    let tags = payload
+        .tags
+        .as_deref()
+        .map(normalize_tags)
+        .unwrap_or_default();
 
-    validate_price.add(
-        "price",
-        ValidationError {
-            code: "range".into(),
-            message: Some("Price must be a non-negative number".into()),
-            params: std::collections::HashMap::new(),
+    let new_product = ProductService::create_product(
+        &state.db,
+        claims.sub,
+        NewProduct {
+            title: payload.title,
+            content: payload.content,
+            price,
+            category_ids,
+            tags,
         },
-    );
+    )
+    .await
+    .map_err(AppError::internal)?;
 
-    let price_decimal =
-        Decimal::from_f64(payload.price).ok_or_else(|| AppError::validation(validate_price))?;
+    Ok((
+        StatusCode::CREATED,
+        Json(product_detail(&state, new_product).await?),
+    ))
+}
 
-    let new_product = ProductService::create_product(
+pub async fn update_product_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
+    Path(product_id): Path<i32>,
+    Json(payload): Json<UpdateProductPayload>,
+) -> Result<(StatusCode, Json<ProductDetailResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let price_decimal = payload
+        .price
+        .map(price_to_decimal)
+        .transpose()
+        .map_err(AppError::validation)?;
+    let tags = payload.tags.as_deref().map(normalize_tags);
+
+    find_product_for_change(&state, &claims, product_id).await?;
+
+    if let Some(category_ids) = &payload.category_ids {
+        ensure_categories_exist(&state, category_ids).await?;
+    }
+
+    let updated_product = ProductService::update_product(
         &state.db,
+        product_id,
         claims.sub,
-        payload.title,
-        payload.content,
-        price_decimal,
+        ProductChanges {
+            title: payload.title,
+            content: payload.content,
+            price: price_decimal,
+            category_ids: payload.category_ids,
+            tags,
+        },
     )
     .await
-    .map_err(|e| AppError::internal(e))?;
-
-    let response = BaseProductResponse {
-        id: new_product.id,
-        owner_id: new_product.owner_id,
-        title: new_product.title,
-        content: new_product.content,
-        price: new_product.price,
-        created_at: new_product.created_at.to_string(),
-        updated_at: new_product.updated_at.to_string(),
-    };
-
-    Ok((StatusCode::CREATED, Json(response)))
+    .map_err(AppError::internal)?
+    .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        Json(product_detail(&state, updated_product).await?),
+    ))
+}
+
+pub async fn delete_product_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
+    Path(product_id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    find_product_for_change(&state, &claims, product_id).await?;
+
+    let deleted = ProductService::delete_product(&state.db, product_id, claims.sub)
+        .await
+        .map_err(AppError::internal)?;
+
+    if !deleted {
+        return Err(AppError::NotFound("Product not found".to_string()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn find_trash_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsRead>,
+    Query(query): Query<PageQuery>,
+) -> Result<(StatusCode, Json<Page<TrashedProductResponse>>), AppError> {
+    let page = PageRequest::try_from(query)?;
+
+    let products = ProductService::find_deleted_products_page(&state.db, claims.sub, page)
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok((StatusCode::OK, Json(products.map(Into::into))))
+}
+
+pub async fn restore_product_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
+    Path(product_id): Path<i32>,
+) -> Result<(StatusCode, Json<BaseProductResponse>), AppError> {
+    let product = ProductService::find_deleted_product_by_id(&state.db, product_id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Product not found in trash".to_string()))?;
+
+    if product.owner_id != claims.sub && !claims.role.grants(UserRole::Admin) {
+        return Err(AppError::Forbidden(
+            "Only the owner can restore this product".to_string(),
+        ));
+    }
+
+    let restored = ProductService::restore_product(&state.db, product_id, claims.sub)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Product not found in trash".to_string()))?;
+
+    Ok((StatusCode::OK, Json(restored.into())))
+}
+
+pub(super) async fn find_product_for_change(
+    state: &AppState,
+    claims: &Claims,
+    product_id: i32,
+) -> Result<product_entity::Model, AppError> {
+    let product = ProductService::find_product_by_id(&state.db, product_id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+    if product.owner_id != claims.sub && !claims.role.grants(UserRole::Admin) {
+        return Err(AppError::Forbidden(
+            "Only the owner can modify this product".to_string(),
+        ));
+    }
+
+    Ok(product)
+}
+
+pub(super) async fn ensure_can_create_products(
+    state: &AppState,
+    claims: &Claims,
+) -> Result<(), AppError> {
+    if !state.account_config.require_verified_email {
+        return Ok(());
+    }
+
+    let user = UserService::find_user_by_id(&state.db, claims.sub)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
+
+    if user.email_verified_at.is_none() {
+        return Err(AppError::Forbidden(
+            "Verify your email address before creating products".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+async fn product_detail(
+    state: &AppState,
+    product: product_entity::Model,
+) -> Result<ProductDetailResponse, AppError> {
+    let categories = CategoryService::find_product_categories(&state.db, &product)
+        .await
+        .map_err(AppError::internal)?;
+    let tags = TagService::find_product_tags(&state.db, &product)
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok(ProductDetailResponse {
+        product: product.into(),
+        categories: categories.into_iter().map(Into::into).collect(),
+        tags: tags.into_iter().map(|tag| tag.name).collect(),
+    })
+}
+
+pub(super) async fn ensure_categories_exist(
+    state: &AppState,
+    category_ids: &[i32],
+) -> Result<(), AppError> {
+    let missing = CategoryService::find_missing_ids(&state.db, category_ids)
+        .await
+        .map_err(AppError::internal)?;
+
+    if !missing.is_empty() {
+        return Err(AppError::invalid_field(
+            "category_ids",
+            "unknown",
+            format!("Unknown category ids: {missing:?}"),
+        ));
+    }
+
+    Ok(())
+}
+
+/// The payload has already been validated, so every name normalizes.
+fn normalize_tags(names: &[String]) -> Vec<String> {
+    normalize_tag_names(names).unwrap_or_default()
 }
@@ -1,8 +1,32 @@
-use rust_decimal::Decimal;
+use std::{borrow::Cow, collections::HashSet, str::FromStr};
+
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use rust_decimal::{Decimal, prelude::FromPrimitive};
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
 
-use crate::modules::user::user_dto::GetUsersResponse;
+use super::{
+    inventory_movement_entity::{self, InventoryReason},
+    product_entity, product_image_entity,
+    product_revision_entity::{self, RevisionAction},
+    product_revision_service::ProductSnapshot,
+    product_transfer::{DataFormat, ImportMode},
+};
+use crate::modules::{
+    category::category_dto::CategoryResponse,
+    shared::{
+        error::AppError,
+        pagination::{
+            PageCursor, PageQuery, PageRequest, decode_parts, encode_parts, invalid, parse_micros,
+        },
+    },
+    tag::tag_service::{MAX_TAG_LENGTH, normalize_tag_name},
+    user::user_dto::GetUsersResponse,
+};
 
 #[derive(Debug, Serialize)]
 pub struct BaseProductResponse {
@@ -11,10 +35,224 @@ pub struct BaseProductResponse {
     pub title: String,
     pub content: Option<String>,
     pub price: Decimal,
+    pub stock: i32,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl From<product_entity::Model> for BaseProductResponse {
+    fn from(product: product_entity::Model) -> Self {
+        BaseProductResponse {
+            id: product.id,
+            owner_id: product.owner_id,
+            title: product.title,
+            content: product.content,
+            price: product.price,
+            stock: product.stock,
+            created_at: product.created_at.to_string(),
+            updated_at: product.updated_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrashedProductResponse {
+    #[serde(flatten)]
+    pub product: BaseProductResponse,
+    pub deleted_at: Option<String>,
+}
+
+impl From<product_entity::Model> for TrashedProductResponse {
+    fn from(product: product_entity::Model) -> Self {
+        TrashedProductResponse {
+            deleted_at: product.deleted_at.map(|t| t.to_string()),
+            product: product.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProductImageResponse {
+    pub id: i32,
+    pub product_id: i32,
+    pub position: i32,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub checksum: String,
+    pub url: String,
+    pub created_at: String,
+}
+
+impl From<product_image_entity::Model> for ProductImageResponse {
+    fn from(image: product_image_entity::Model) -> Self {
+        ProductImageResponse {
+            url: format!("/api/products/{}/images/{}", image.product_id, image.id),
+            id: image.id,
+            product_id: image.product_id,
+            position: image.position,
+            content_type: image.content_type,
+            size_bytes: image.size_bytes,
+            checksum: image.checksum,
+            created_at: image.created_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InventoryMovementResponse {
+    pub id: i32,
+    pub product_id: i32,
+    pub actor_id: Option<i32>,
+    pub reason: InventoryReason,
+    pub delta: i32,
+    pub stock_after: i32,
+    pub reservation_id: Option<i32>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+impl From<inventory_movement_entity::Model> for InventoryMovementResponse {
+    fn from(movement: inventory_movement_entity::Model) -> Self {
+        InventoryMovementResponse {
+            id: movement.id,
+            product_id: movement.product_id,
+            actor_id: movement.actor_id,
+            reason: movement.reason,
+            delta: movement.delta,
+            stock_after: movement.stock_after,
+            reservation_id: movement.reservation_id,
+            note: movement.note,
+            created_at: movement.created_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProductRevisionResponse {
+    pub id: i32,
+    pub product_id: i32,
+    pub actor_id: Option<i32>,
+    pub action: RevisionAction,
+    pub changes: serde_json::Value,
+    pub created_at: String,
+}
+
+impl From<product_revision_entity::Model> for ProductRevisionResponse {
+    fn from(revision: product_revision_entity::Model) -> Self {
+        ProductRevisionResponse {
+            id: revision.id,
+            product_id: revision.product_id,
+            actor_id: revision.actor_id,
+            action: revision.action,
+            changes: revision.changes,
+            created_at: revision.created_at.to_string(),
+        }
+    }
+}
+
+/// The product as it stood at `at`, taken from the revision in effect then.
+#[derive(Debug, Serialize)]
+pub struct ProductSnapshotResponse {
+    pub product_id: i32,
+    pub revision_id: i32,
+    pub at: String,
+    pub revised_at: String,
+    #[serde(flatten)]
+    pub snapshot: ProductSnapshot,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnapshotQuery {
+    pub at: String,
+}
+
+impl SnapshotQuery {
+    pub fn timestamp(&self) -> Result<NaiveDateTime, AppError> {
+        let mut errors = ValidationErrors::new();
+        parse_timestamp(&mut errors, "at", &self.at).ok_or_else(|| AppError::validation(errors))
+    }
+}
+
+pub const MAX_STOCK_CHANGE: i32 = 1_000_000;
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct AdjustStockPayload {
+    #[validate(
+        range(min = -MAX_STOCK_CHANGE, max = MAX_STOCK_CHANGE),
+        custom(function = "validate_non_zero")
+    )]
+    pub delta: i32,
+
+    #[validate(custom(function = "validate_manual_reason"))]
+    pub reason: InventoryReason,
+
+    #[validate(length(max = 500))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct ReserveStockPayload {
+    #[validate(range(min = 1, max = MAX_STOCK_CHANGE))]
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseStockPayload {
+    pub reservation_id: i32,
+}
+
+fn validate_non_zero(delta: i32) -> Result<(), ValidationError> {
+    if delta == 0 {
+        return Err(invalid("non_zero", "Must not be zero".to_string()));
+    }
+    Ok(())
+}
+
+fn validate_manual_reason(reason: &InventoryReason) -> Result<(), ValidationError> {
+    if !reason.is_manual() {
+        return Err(invalid(
+            "reason",
+            "Reservations and releases have their own endpoints".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub mode: ImportMode,
+    /// Overrides the format implied by the `Content-Type` header.
+    pub format: Option<DataFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    pub format: DataFormat,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub mode: ImportMode,
+    pub total_rows: usize,
+    pub imported: usize,
+    pub failed: usize,
+    pub created: Vec<ImportedRow>,
+    pub errors: Vec<ImportRowError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportedRow {
+    pub line: u64,
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRowError {
+    pub line: u64,
+    pub errors: ValidationErrors,
+}
+
 #[derive(Debug, Serialize)]
 pub struct GetProductsResponse {
     #[serde(flatten)]
@@ -22,6 +260,17 @@ pub struct GetProductsResponse {
     pub owner: Option<GetUsersResponse>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ProductDetailResponse {
+    #[serde(flatten)]
+    pub product: BaseProductResponse,
+    pub categories: Vec<CategoryResponse>,
+    pub tags: Vec<String>,
+}
+
+pub const MAX_PRODUCT_TAGS: u64 = 20;
+pub const MAX_PRODUCT_CATEGORIES: u64 = 10;
+
 #[derive(Debug, Validate, Deserialize)]
 pub struct CreateProductPayload {
     #[validate(length(min = 1, max = 100))]
@@ -31,8 +280,16 @@ pub struct CreateProductPayload {
 
     #[validate(range(min = 0.0))]
     pub price: f64,
+
+    #[validate(length(max = MAX_PRODUCT_CATEGORIES))]
+    pub category_ids: Option<Vec<i32>>,
+
+    /// Tag names; tags that do not exist yet are created.
+    #[validate(length(max = MAX_PRODUCT_TAGS), custom(function = "validate_tag_names"))]
+    pub tags: Option<Vec<String>>,
 }
 
+/// `category_ids` and `tags` replace the current sets when given.
 #[derive(Debug, Validate, Deserialize)]
 pub struct UpdateProductPayload {
     #[validate(length(min = 1, max = 100))]
@@ -42,4 +299,501 @@ pub struct UpdateProductPayload {
 
     #[validate(range(min = 0.0))]
     pub price: Option<f64>,
+
+    #[validate(length(max = MAX_PRODUCT_CATEGORIES))]
+    pub category_ids: Option<Vec<i32>>,
+
+    #[validate(length(max = MAX_PRODUCT_TAGS), custom(function = "validate_tag_names"))]
+    pub tags: Option<Vec<String>>,
+}
+
+pub fn price_to_decimal(price: f64) -> Result<Decimal, ValidationErrors> {
+    Decimal::from_f64(price).ok_or_else(|| {
+        let mut errors = ValidationErrors::new();
+        errors.add(
+            "price",
+            invalid("range", "Price must be a non-negative number".to_string()),
+        );
+        errors
+    })
+}
+
+fn validate_tag_names(names: &[String]) -> Result<(), ValidationError> {
+    if names.iter().all(|name| normalize_tag_name(name).is_some()) {
+        Ok(())
+    } else {
+        Err(invalid(
+            "tag",
+            format!("Tags must be between 1 and {MAX_TAG_LENGTH} characters"),
+        ))
+    }
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct ProductSearchQuery {
+    #[validate(length(min = 1, max = 200))]
+    pub q: String,
+    pub limit: Option<u64>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+}
+
+impl ProductSearchQuery {
+    pub fn page_query(&self) -> PageQuery {
+        PageQuery {
+            limit: self.limit,
+            after: self.after.clone(),
+            before: self.before.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProductSearchResponse {
+    #[serde(flatten)]
+    pub product: BaseProductResponse,
+    pub rank: f32,
+    pub title_highlight: String,
+    pub snippet: Option<String>,
+}
+
+/// Deepest offset a search cursor may point at. Nobody pages this far through
+/// relevance-ranked results, and Postgres would scan every skipped row.
+pub const MAX_SEARCH_OFFSET: u64 = 10_000;
+
+/// Search results are ordered by relevance, which has no stable keyset, so
+/// their cursors wrap a plain offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchCursor {
+    pub offset: u64,
+}
+
+impl PageCursor for SearchCursor {
+    fn encode(&self) -> String {
+        encode_parts(&[self.offset.to_string()])
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let [offset] = decode_parts(value)?;
+        let offset = offset.parse().ok()?;
+
+        (offset <= MAX_SEARCH_OFFSET).then_some(SearchCursor { offset })
+    }
+}
+
+const MAX_SEARCH_LENGTH: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProductSortField {
+    Price,
+    CreatedAt,
+}
+
+impl FromStr for ProductSortField {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "price" => Ok(ProductSortField::Price),
+            "created_at" => Ok(ProductSortField::CreatedAt),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProductSort {
+    pub field: ProductSortField,
+    pub descending: bool,
+}
+
+/// `created_after` is inclusive and `created_before` exclusive, so adjacent
+/// ranges never overlap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProductFilter {
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+    pub owner_id: Option<i32>,
+    pub created_after: Option<NaiveDateTime>,
+    pub created_before: Option<NaiveDateTime>,
+    pub q: Option<String>,
+    pub category_id: Option<i32>,
+    pub tag: Option<String>,
+}
+
+/// Carries every column a product listing can be ordered by, so one cursor
+/// type works for all whitelisted sorts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProductCursor {
+    pub price: Decimal,
+    pub created_at: NaiveDateTime,
+    pub id: i32,
+}
+
+impl From<&product_entity::Model> for ProductCursor {
+    fn from(product: &product_entity::Model) -> Self {
+        ProductCursor {
+            price: product.price,
+            created_at: product.created_at,
+            id: product.id,
+        }
+    }
+}
+
+impl PageCursor for ProductCursor {
+    fn encode(&self) -> String {
+        encode_parts(&[
+            self.price.to_string(),
+            self.created_at.and_utc().timestamp_micros().to_string(),
+            self.id.to_string(),
+        ])
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let [price, created_at, id] = decode_parts(value)?;
+
+        Some(ProductCursor {
+            price: price.parse().ok()?,
+            created_at: parse_micros(&created_at)?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ProductListQuery {
+    pub filter: ProductFilter,
+    pub sort: Vec<ProductSort>,
+    pub page: PageRequest<ProductCursor>,
+}
+
+impl ProductListQuery {
+    pub fn parse(params: Vec<(String, String)>) -> Result<Self, ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let mut filter = ProductFilter::default();
+        let mut sort = Vec::new();
+        let mut page = PageQuery::default();
+        let mut seen = HashSet::new();
+
+        for (key, value) in params {
+            if !seen.insert(key.clone()) {
+                add_error(
+                    &mut errors,
+                    key,
+                    invalid("duplicate", "Parameter given more than once".to_string()),
+                );
+                continue;
+            }
+
+            match key.as_str() {
+                "limit" => match value.parse() {
+                    Ok(limit) => page.limit = Some(limit),
+                    Err(_) => errors.add("limit", invalid("type", "Expected an integer".into())),
+                },
+                "after" => page.after = Some(value),
+                "before" => page.before = Some(value),
+                "min_price" => filter.min_price = parse_price(&mut errors, "min_price", &value),
+                "max_price" => filter.max_price = parse_price(&mut errors, "max_price", &value),
+                "owner_id" => match value.parse() {
+                    Ok(owner_id) => filter.owner_id = Some(owner_id),
+                    Err(_) => errors.add("owner_id", invalid("type", "Expected an integer".into())),
+                },
+                "created_after" => {
+                    filter.created_after = parse_timestamp(&mut errors, "created_after", &value)
+                }
+                "created_before" => {
+                    filter.created_before = parse_timestamp(&mut errors, "created_before", &value)
+                }
+                "q" => {
+                    let q = value.trim();
+                    if q.is_empty() || q.chars().count() > MAX_SEARCH_LENGTH {
+                        errors.add(
+                            "q",
+                            invalid(
+                                "length",
+                                format!("Must be between 1 and {MAX_SEARCH_LENGTH} characters"),
+                            ),
+                        );
+                    } else {
+                        filter.q = Some(q.to_string());
+                    }
+                }
+                "category_id" => match value.parse() {
+                    Ok(category_id) => filter.category_id = Some(category_id),
+                    Err(_) => {
+                        errors.add("category_id", invalid("type", "Expected an integer".into()))
+                    }
+                },
+                "tag" => match normalize_tag_name(&value) {
+                    Some(tag) => filter.tag = Some(tag),
+                    None => errors.add(
+                        "tag",
+                        invalid(
+                            "length",
+                            format!("Must be between 1 and {MAX_TAG_LENGTH} characters"),
+                        ),
+                    ),
+                },
+                "sort" => match parse_sort(&value) {
+                    Ok(parsed) => sort = parsed,
+                    Err(error) => errors.add("sort", error),
+                },
+                _ => add_error(
+                    &mut errors,
+                    key,
+                    invalid("unknown", "Unknown query parameter".to_string()),
+                ),
+            }
+        }
+
+        if let (Some(min), Some(max)) = (filter.min_price, filter.max_price)
+            && min > max
+        {
+            errors.add(
+                "max_price",
+                invalid("range", "Must not be less than min_price".to_string()),
+            );
+        }
+
+        if let (Some(after), Some(before)) = (filter.created_after, filter.created_before)
+            && after >= before
+        {
+            errors.add(
+                "created_before",
+                invalid("range", "Must be later than created_after".to_string()),
+            );
+        }
+
+        let page = PageRequest::parse(page).map_err(|page_errors| {
+            errors.errors_mut().extend(page_errors.into_errors());
+        });
+
+        match page {
+            Ok(page) if errors.is_empty() => Ok(ProductListQuery { filter, sort, page }),
+            _ => Err(errors),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ProductListQuery {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) =
+            Query::<Vec<(String, String)>>::try_from_uri(&parts.uri).map_err(|_| {
+                let mut errors = ValidationErrors::new();
+                errors.add(
+                    "query",
+                    invalid("malformed", "Malformed query string".into()),
+                );
+                AppError::validation(errors)
+            })?;
+
+        ProductListQuery::parse(params).map_err(AppError::validation)
+    }
+}
+
+fn add_error(errors: &mut ValidationErrors, field: String, error: ValidationError) {
+    let entry = errors
+        .errors_mut()
+        .entry(Cow::Owned(field))
+        .or_insert_with(|| ValidationErrorsKind::Field(Vec::new()));
+
+    if let ValidationErrorsKind::Field(field_errors) = entry {
+        field_errors.push(error);
+    }
+}
+
+fn parse_price(errors: &mut ValidationErrors, field: &'static str, value: &str) -> Option<Decimal> {
+    match Decimal::from_str(value) {
+        Ok(price) if !price.is_sign_negative() => Some(price),
+        _ => {
+            errors.add(
+                field,
+                invalid("range", "Price must be a non-negative number".into()),
+            );
+            None
+        }
+    }
+}
+
+/// Accepts RFC 3339 timestamps or plain `YYYY-MM-DD` dates (midnight UTC).
+fn parse_timestamp(
+    errors: &mut ValidationErrors,
+    field: &'static str,
+    value: &str,
+) -> Option<NaiveDateTime> {
+    let parsed = DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.naive_utc())
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+        });
+
+    match parsed {
+        Ok(timestamp) => Some(timestamp),
+        Err(_) => {
+            errors.add(
+                field,
+                invalid("format", "Expected an RFC 3339 timestamp or a date".into()),
+            );
+            None
+        }
+    }
+}
+
+fn parse_sort(value: &str) -> Result<Vec<ProductSort>, ValidationError> {
+    let mut sort: Vec<ProductSort> = Vec::new();
+
+    for part in value.split(',') {
+        let (name, descending) = match part.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (part, false),
+        };
+
+        let field: ProductSortField = name.parse().map_err(|_| {
+            invalid(
+                "unknown",
+                format!("Cannot sort by `{part}`; allowed fields are price and created_at"),
+            )
+        })?;
+
+        if sort.iter().any(|existing| existing.field == field) {
+            return Err(invalid(
+                "duplicate",
+                format!("`{name}` appears more than once"),
+            ));
+        }
+
+        sort.push(ProductSort { field, descending });
+    }
+
+    Ok(sort)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn error_fields(result: Result<ProductListQuery, ValidationErrors>) -> Vec<String> {
+        let mut fields: Vec<String> = result
+            .unwrap_err()
+            .into_errors()
+            .into_keys()
+            .map(|field| field.into_owned())
+            .collect();
+        fields.sort();
+        fields
+    }
+
+    #[test]
+    fn test_parse_product_list_query() {
+        let query = ProductListQuery::parse(params(&[
+            ("min_price", "10"),
+            ("max_price", "99.50"),
+            ("owner_id", "3"),
+            ("created_after", "2025-01-01"),
+            ("created_before", "2025-02-01T12:00:00+02:00"),
+            ("q", "  lamp "),
+            ("category_id", "4"),
+            ("tag", " Outdoor  Lighting"),
+            ("sort", "price,-created_at"),
+            ("limit", "5"),
+        ]))
+        .unwrap();
+
+        assert_eq!(query.filter.min_price, Some(Decimal::new(10, 0)));
+        assert_eq!(query.filter.max_price, Some(Decimal::new(9950, 2)));
+        assert_eq!(query.filter.owner_id, Some(3));
+        assert_eq!(
+            query.filter.created_before.unwrap().to_string(),
+            "2025-02-01 10:00:00"
+        );
+        assert_eq!(query.filter.q.as_deref(), Some("lamp"));
+        assert_eq!(query.filter.category_id, Some(4));
+        assert_eq!(query.filter.tag.as_deref(), Some("outdoor lighting"));
+        assert_eq!(
+            query.sort,
+            vec![
+                ProductSort {
+                    field: ProductSortField::Price,
+                    descending: false,
+                },
+                ProductSort {
+                    field: ProductSortField::CreatedAt,
+                    descending: true,
+                },
+            ]
+        );
+        assert_eq!(query.page.limit, 5);
+    }
+
+    #[test]
+    fn test_parse_product_list_query_collects_all_errors() {
+        let fields = error_fields(ProductListQuery::parse(params(&[
+            ("min_price", "-1"),
+            ("owner_id", "abc"),
+            ("created_after", "yesterday"),
+            ("sort", "title"),
+            ("limit", "0"),
+            ("colour", "red"),
+        ])));
+
+        assert_eq!(
+            fields,
+            vec![
+                "colour",
+                "created_after",
+                "limit",
+                "min_price",
+                "owner_id",
+                "sort"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_product_list_query_checks_ranges() {
+        let fields = error_fields(ProductListQuery::parse(params(&[
+            ("min_price", "20"),
+            ("max_price", "10"),
+            ("created_after", "2025-02-01"),
+            ("created_before", "2025-01-01"),
+        ])));
+
+        assert_eq!(fields, vec!["created_before", "max_price"]);
+    }
+
+    #[test]
+    fn test_parse_sort_rejects_duplicates() {
+        assert!(parse_sort("price,-price").is_err());
+        assert!(parse_sort("").is_err());
+    }
+
+    #[test]
+    fn test_product_cursor_round_trip() {
+        let cursor = ProductCursor {
+            price: Decimal::new(1999, 2),
+            created_at: parse_micros("1700000000123456").unwrap(),
+            id: 9,
+        };
+
+        assert_eq!(ProductCursor::decode(&cursor.encode()), Some(cursor));
+    }
+
+    #[test]
+    fn test_search_cursor_rejects_offsets_past_the_cap() {
+        let cursor = SearchCursor { offset: 40 };
+        assert_eq!(SearchCursor::decode(&cursor.encode()), Some(cursor));
+
+        let too_deep = SearchCursor { offset: u64::MAX };
+        assert_eq!(SearchCursor::decode(&too_deep.encode()), None);
+    }
 }
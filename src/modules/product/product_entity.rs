@@ -3,7 +3,12 @@ use sea_orm::{ActiveValue::Set, entity::prelude::*};
 use serde::{Deserialize, Serialize};
 use sqlx::types::chrono;
 
-use crate::modules::user::user_entity;
+use super::product_image_entity;
+use crate::modules::{
+    category::{category_entity, product_category_entity},
+    tag::{product_tag_entity, tag_entity},
+    user::user_entity,
+};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "products")]
@@ -14,8 +19,10 @@ pub struct Model {
     pub title: String,
     pub content: Option<String>,
     pub price: Decimal,
+    pub stock: i32,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    pub deleted_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -28,6 +35,8 @@ pub enum Relation {
         on_update = "Cascade"
     )]
     User,
+    #[sea_orm(has_many = "product_image_entity::Entity")]
+    Images,
 }
 
 impl Related<user_entity::Entity> for Entity {
@@ -36,6 +45,32 @@ impl Related<user_entity::Entity> for Entity {
     }
 }
 
+impl Related<product_image_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Images.def()
+    }
+}
+
+impl Related<category_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        product_category_entity::Relation::Category.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(product_category_entity::Relation::Product.def().rev())
+    }
+}
+
+impl Related<tag_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        product_tag_entity::Relation::Tag.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(product_tag_entity::Relation::Product.def().rev())
+    }
+}
+
 #[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
     async fn before_save<C>(mut self, _db: &C, _insert: bool) -> Result<Self, DbErr>
@@ -0,0 +1,254 @@
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::{Multipart, Path, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use sea_orm::TransactionTrait;
+use sha2::{Digest, Sha256};
+use tokio_util::io::ReaderStream;
+
+use super::{
+    product_controller::find_product_for_change,
+    product_dto::ProductImageResponse,
+    product_image_service::{ProductImageService, sniff_image_type},
+    product_service::ProductService,
+};
+use crate::{
+    middleware::{ProductsRead, ProductsWrite, RequireScope},
+    modules::shared::error::AppError,
+    state::AppState,
+    utils::token::generate_opaque_token,
+};
+
+const MAX_FILES_PER_UPLOAD: usize = 10;
+/// Room for multipart boundaries, part headers and small non-file fields.
+const UPLOAD_OVERHEAD_BYTES: usize = 64 * 1024;
+
+/// The request body limit for uploads: a full batch of maximum-size images.
+pub fn max_upload_bytes(max_image_bytes: usize) -> usize {
+    MAX_FILES_PER_UPLOAD * max_image_bytes + UPLOAD_OVERHEAD_BYTES
+}
+
+struct Upload {
+    bytes: Bytes,
+    content_type: &'static str,
+    checksum: String,
+}
+
+pub async fn find_images_handler(
+    State(state): State<AppState>,
+    _scope: RequireScope<ProductsRead>,
+    Path(product_id): Path<i32>,
+) -> Result<(StatusCode, Json<Vec<ProductImageResponse>>), AppError> {
+    ProductService::find_product_by_id(&state.db, product_id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+    let images = ProductImageService::find_images(&state.db, product_id)
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(images.into_iter().map(Into::into).collect()),
+    ))
+}
+
+/// Accepts one or more `file` parts. Every file is checked before any is
+/// stored, and the images are attached in one transaction, so a rejected or
+/// failed upload leaves the product unchanged.
+pub async fn upload_images_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
+    Path(product_id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<Vec<ProductImageResponse>>), AppError> {
+    find_product_for_change(&state, &claims, product_id).await?;
+
+    let max_bytes = state.product_config.max_image_bytes;
+    let mut uploads = Vec::new();
+
+    while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        if uploads.len() == MAX_FILES_PER_UPLOAD {
+            return Err(AppError::invalid_field(
+                "file",
+                "count",
+                format!("At most {MAX_FILES_PER_UPLOAD} files can be uploaded at once"),
+            ));
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(AppError::invalid_field(
+                    "file",
+                    "size",
+                    format!("Images must be at most {max_bytes} bytes"),
+                ));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        let content_type = sniff_image_type(&bytes).ok_or_else(|| {
+            AppError::invalid_field(
+                "file",
+                "content_type",
+                "Only JPEG, PNG, GIF and WebP images are accepted",
+            )
+        })?;
+
+        uploads.push(Upload {
+            checksum: hex::encode(Sha256::digest(&bytes)),
+            bytes: Bytes::from(bytes),
+            content_type,
+        });
+    }
+
+    if uploads.is_empty() {
+        return Err(AppError::invalid_field(
+            "file",
+            "required",
+            "Attach at least one image as a `file` part",
+        ));
+    }
+
+    let mut stored_keys = Vec::with_capacity(uploads.len());
+    let result = store_images(&state, product_id, uploads, &mut stored_keys).await;
+    if result.is_err() {
+        for key in &stored_keys {
+            if let Err(e) = state.storage.delete(key).await {
+                tracing::warn!("Failed to remove orphaned image {key}: {e}");
+            }
+        }
+    }
+    let created = result.map_err(AppError::internal)?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// Stores every upload, then records them all in one transaction. The keys
+/// stored so far are left in `stored_keys` for the caller to clean up if
+/// this fails.
+async fn store_images(
+    state: &AppState,
+    product_id: i32,
+    uploads: Vec<Upload>,
+    stored_keys: &mut Vec<String>,
+) -> anyhow::Result<Vec<ProductImageResponse>> {
+    let mut stored = Vec::with_capacity(uploads.len());
+    for upload in uploads {
+        let key = format!("products/{product_id}/{}", generate_opaque_token());
+        state.storage.put(&key, upload.bytes.clone()).await?;
+        stored_keys.push(key.clone());
+        stored.push((key, upload));
+    }
+
+    let txn = state.db.begin().await?;
+    let mut created = Vec::with_capacity(stored.len());
+    for (key, upload) in stored {
+        let image = ProductImageService::create_image(
+            &txn,
+            product_id,
+            key,
+            upload.content_type.to_string(),
+            upload.bytes.len() as i64,
+            upload.checksum,
+        )
+        .await?;
+        created.push(image.into());
+    }
+    txn.commit().await?;
+
+    Ok(created)
+}
+
+pub async fn download_image_handler(
+    State(state): State<AppState>,
+    _scope: RequireScope<ProductsRead>,
+    Path((product_id, image_id)): Path<(i32, i32)>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    ProductService::find_product_by_id(&state.db, product_id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+    let image = ProductImageService::find_image(&state.db, product_id, image_id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Image not found".to_string()))?;
+
+    let etag = format!("\"{}\"", image.checksum);
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    let object = state
+        .storage
+        .get(&image.storage_key)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Image not found".to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, image.content_type),
+            (header::CONTENT_LENGTH, object.size.to_string()),
+            (header::ETAG, etag),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        Body::from_stream(ReaderStream::new(object.reader)),
+    )
+        .into_response())
+}
+
+pub async fn delete_image_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
+    Path((product_id, image_id)): Path<(i32, i32)>,
+) -> Result<StatusCode, AppError> {
+    find_product_for_change(&state, &claims, product_id).await?;
+
+    let image = ProductImageService::find_image(&state.db, product_id, image_id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Image not found".to_string()))?;
+
+    let deleted = ProductImageService::delete_image(&state.db, image.id)
+        .await
+        .map_err(AppError::internal)?;
+
+    if !deleted {
+        return Err(AppError::NotFound("Image not found".to_string()));
+    }
+
+    if let Err(e) = state.storage.delete(&image.storage_key).await {
+        tracing::warn!("Failed to remove image {}: {e}", image.storage_key);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn multipart_error(e: axum::extract::multipart::MultipartError) -> AppError {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        return AppError::invalid_field(
+            "file",
+            "size",
+            "The upload exceeds the request size limit",
+        );
+    }
+    AppError::invalid_field("file", "multipart", e.body_text())
+}
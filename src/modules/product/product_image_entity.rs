@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::product_entity;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "product_images")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub product_id: i32,
+    pub position: i32,
+    #[sea_orm(unique)]
+    pub storage_key: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub checksum: String,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "product_entity::Entity",
+        from = "Column::ProductId",
+        to = "product_entity::Column::Id",
+        on_delete = "Cascade",
+        on_update = "Cascade"
+    )]
+    Product,
+}
+
+impl Related<product_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Product.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
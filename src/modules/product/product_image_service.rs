@@ -0,0 +1,128 @@
+use anyhow::Result;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+
+use super::{product_entity, product_image_entity};
+
+#[derive(Clone)]
+pub struct ProductImageService;
+
+impl ProductImageService {
+    pub async fn find_images(
+        db: &DatabaseConnection,
+        product_id: i32,
+    ) -> Result<Vec<product_image_entity::Model>> {
+        let images = product_image_entity::Entity::find()
+            .filter(product_image_entity::Column::ProductId.eq(product_id))
+            .order_by_asc(product_image_entity::Column::Position)
+            .order_by_asc(product_image_entity::Column::Id)
+            .all(db)
+            .await?;
+        Ok(images)
+    }
+
+    pub async fn find_image(
+        db: &DatabaseConnection,
+        product_id: i32,
+        image_id: i32,
+    ) -> Result<Option<product_image_entity::Model>> {
+        let image = product_image_entity::Entity::find_by_id(image_id)
+            .filter(product_image_entity::Column::ProductId.eq(product_id))
+            .one(db)
+            .await?;
+        Ok(image)
+    }
+
+    /// Records an already stored object as the product's last image. Run it
+    /// in a transaction: the product row is locked so concurrent uploads take
+    /// distinct positions.
+    pub async fn create_image<C: ConnectionTrait>(
+        db: &C,
+        product_id: i32,
+        storage_key: String,
+        content_type: String,
+        size_bytes: i64,
+        checksum: String,
+    ) -> Result<product_image_entity::Model> {
+        product_entity::Entity::find_by_id(product_id)
+            .lock_exclusive()
+            .one(db)
+            .await?;
+
+        let last_position: Option<i32> = product_image_entity::Entity::find()
+            .select_only()
+            .column_as(product_image_entity::Column::Position.max(), "position")
+            .filter(product_image_entity::Column::ProductId.eq(product_id))
+            .into_tuple()
+            .one(db)
+            .await?
+            .flatten();
+
+        let image = product_image_entity::ActiveModel {
+            product_id: Set(product_id),
+            position: Set(last_position.map_or(0, |position| position + 1)),
+            storage_key: Set(storage_key),
+            content_type: Set(content_type),
+            size_bytes: Set(size_bytes),
+            checksum: Set(checksum),
+            ..Default::default()
+        };
+
+        Ok(image.insert(db).await?)
+    }
+
+    pub async fn delete_image(db: &DatabaseConnection, image_id: i32) -> Result<bool> {
+        let result = product_image_entity::Entity::delete_by_id(image_id)
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+}
+
+/// Identifies an image by its magic bytes rather than trusting the
+/// client-supplied content type.
+pub fn sniff_image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_image_type() {
+        assert_eq!(
+            sniff_image_type(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00]),
+            Some("image/jpeg")
+        );
+        assert_eq!(
+            sniff_image_type(b"\x89PNG\r\n\x1a\n\x00\x00"),
+            Some("image/png")
+        );
+        assert_eq!(sniff_image_type(b"GIF89a\x01\x00"), Some("image/gif"));
+        assert_eq!(
+            sniff_image_type(b"RIFF\x24\x00\x00\x00WEBPVP8 "),
+            Some("image/webp")
+        );
+    }
+
+    #[test]
+    fn test_sniff_image_type_rejects_other_files() {
+        assert_eq!(sniff_image_type(b""), None);
+        assert_eq!(sniff_image_type(b"<svg xmlns=\"\"></svg>"), None);
+        assert_eq!(sniff_image_type(b"RIFF\x24\x00\x00\x00WAVE"), None);
+        assert_eq!(sniff_image_type(b"%PDF-1.7"), None);
+    }
+}
@@ -0,0 +1,93 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+
+use super::{
+    product_dto::{ProductRevisionResponse, ProductSnapshotResponse, SnapshotQuery},
+    product_revision_service::{ProductRevisionService, ProductSnapshot},
+    product_service::ProductService,
+};
+use crate::{
+    middleware::{ProductsRead, RequireScope},
+    modules::{
+        shared::{
+            error::AppError,
+            pagination::{Page, PageQuery, PageRequest},
+        },
+        user::user_entity::UserRole,
+    },
+    state::AppState,
+    utils::auth::Claims,
+};
+
+pub async fn find_history_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsRead>,
+    Path(product_id): Path<i32>,
+    Query(query): Query<PageQuery>,
+) -> Result<(StatusCode, Json<Page<ProductRevisionResponse>>), AppError> {
+    let page = PageRequest::try_from(query)?;
+
+    ensure_can_view_history(&state, &claims, product_id).await?;
+
+    let revisions = ProductRevisionService::find_revisions_page(&state.db, product_id, page)
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok((StatusCode::OK, Json(revisions.map(Into::into))))
+}
+
+pub async fn find_snapshot_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsRead>,
+    Path(product_id): Path<i32>,
+    Query(query): Query<SnapshotQuery>,
+) -> Result<(StatusCode, Json<ProductSnapshotResponse>), AppError> {
+    let at = query.timestamp()?;
+
+    ensure_can_view_history(&state, &claims, product_id).await?;
+
+    let revision = ProductRevisionService::find_revision_at(&state.db, product_id, at)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| {
+            AppError::NotFound("No revision of this product at that time".to_string())
+        })?;
+
+    let snapshot: ProductSnapshot =
+        serde_json::from_value(revision.snapshot).map_err(AppError::internal)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ProductSnapshotResponse {
+            product_id,
+            revision_id: revision.id,
+            at: at.to_string(),
+            revised_at: revision.created_at.to_string(),
+            snapshot,
+        }),
+    ))
+}
+
+/// History stays readable while the product is in the trash, but only to
+/// its owner and admins.
+async fn ensure_can_view_history(
+    state: &AppState,
+    claims: &Claims,
+    product_id: i32,
+) -> Result<(), AppError> {
+    let product = ProductService::find_product_including_deleted(&state.db, product_id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+    if product.owner_id != claims.sub && !claims.role.grants(UserRole::Admin) {
+        return Err(AppError::Forbidden(
+            "Only the owner can view this product's history".to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::product_entity;
+use crate::modules::user::user_entity;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "product_revisions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub product_id: i32,
+    pub actor_id: Option<i32>,
+    pub action: RevisionAction,
+    /// `{field: {"old": .., "new": ..}}` for every field the change touched.
+    pub changes: Json,
+    /// The product's tracked fields after the change.
+    pub snapshot: Json,
+    pub created_at: DateTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionAction {
+    #[sea_orm(string_value = "created")]
+    Created,
+    #[sea_orm(string_value = "updated")]
+    Updated,
+    #[sea_orm(string_value = "deleted")]
+    Deleted,
+    #[sea_orm(string_value = "restored")]
+    Restored,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "product_entity::Entity",
+        from = "Column::ProductId",
+        to = "product_entity::Column::Id",
+        on_delete = "Cascade",
+        on_update = "Cascade"
+    )]
+    Product,
+    #[sea_orm(
+        belongs_to = "user_entity::Entity",
+        from = "Column::ActorId",
+        to = "user_entity::Column::Id",
+        on_delete = "SetNull",
+        on_update = "Cascade"
+    )]
+    Actor,
+}
+
+impl Related<product_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Product.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
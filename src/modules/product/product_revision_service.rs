@@ -0,0 +1,208 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use super::{
+    product_entity,
+    product_revision_entity::{self, RevisionAction},
+};
+use crate::modules::{
+    category::category_service::CategoryService,
+    shared::pagination::{Cursor, Page, PageRequest},
+    tag::tag_service::TagService,
+};
+
+/// The fields a revision tracks. Stock is left out: the inventory ledger
+/// already records every stock movement.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductSnapshot {
+    pub title: String,
+    pub content: Option<String>,
+    pub price: Decimal,
+    pub category_ids: Vec<i32>,
+    pub tags: Vec<String>,
+    pub deleted: bool,
+}
+
+#[derive(Clone)]
+pub struct ProductRevisionService;
+
+impl ProductRevisionService {
+    /// The product's current tracked fields, read through `db` so callers
+    /// inside a transaction see their own uncommitted changes.
+    pub async fn snapshot<C: ConnectionTrait>(
+        db: &C,
+        product: &product_entity::Model,
+    ) -> Result<ProductSnapshot> {
+        let mut category_ids: Vec<i32> = CategoryService::find_product_categories(db, product)
+            .await?
+            .into_iter()
+            .map(|category| category.id)
+            .collect();
+        category_ids.sort_unstable();
+
+        let tags = TagService::find_product_tags(db, product)
+            .await?
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+
+        Ok(ProductSnapshot {
+            title: product.title.clone(),
+            content: product.content.clone(),
+            price: product.price,
+            category_ids,
+            tags,
+            deleted: product.deleted_at.is_some(),
+        })
+    }
+
+    /// Writes a revision for the change from `before` to `after`. Call it in
+    /// the same transaction as the change itself. Updates that change none
+    /// of the tracked fields are not recorded.
+    pub async fn record<C: ConnectionTrait>(
+        db: &C,
+        product_id: i32,
+        actor_id: Option<i32>,
+        action: RevisionAction,
+        before: Option<&ProductSnapshot>,
+        after: &ProductSnapshot,
+    ) -> Result<Option<product_revision_entity::Model>> {
+        let changes = diff(before, after);
+        if action == RevisionAction::Updated && changes.is_empty() {
+            return Ok(None);
+        }
+
+        let revision = product_revision_entity::ActiveModel {
+            product_id: Set(product_id),
+            actor_id: Set(actor_id),
+            action: Set(action),
+            changes: Set(Value::Object(changes)),
+            snapshot: Set(serde_json::to_value(after)?),
+            ..Default::default()
+        };
+
+        Ok(Some(revision.insert(db).await?))
+    }
+
+    /// The product's revisions, newest first.
+    pub async fn find_revisions_page(
+        db: &DatabaseConnection,
+        product_id: i32,
+        page: PageRequest,
+    ) -> Result<Page<product_revision_entity::Model>> {
+        let revisions = page
+            .apply(
+                product_revision_entity::Entity::find()
+                    .filter(product_revision_entity::Column::ProductId.eq(product_id)),
+                product_revision_entity::Column::CreatedAt,
+                product_revision_entity::Column::Id,
+            )
+            .all(db)
+            .await?;
+
+        Ok(page.into_page(revisions, |revision| Cursor {
+            created_at: revision.created_at,
+            id: revision.id,
+        }))
+    }
+
+    /// The latest revision made at or before `at`, i.e. the product as it
+    /// stood then. `None` if the product had no revisions yet.
+    pub async fn find_revision_at(
+        db: &DatabaseConnection,
+        product_id: i32,
+        at: NaiveDateTime,
+    ) -> Result<Option<product_revision_entity::Model>> {
+        let revision = product_revision_entity::Entity::find()
+            .filter(product_revision_entity::Column::ProductId.eq(product_id))
+            .filter(product_revision_entity::Column::CreatedAt.lte(at))
+            .order_by_desc(product_revision_entity::Column::CreatedAt)
+            .order_by_desc(product_revision_entity::Column::Id)
+            .one(db)
+            .await?;
+        Ok(revision)
+    }
+}
+
+/// `{field: {"old": .., "new": ..}}` for every field that differs. Without
+/// `before` (an insert) every field that has a value is listed.
+pub fn diff(before: Option<&ProductSnapshot>, after: &ProductSnapshot) -> Map<String, Value> {
+    let before = before.map(fields).unwrap_or_default();
+
+    fields(after)
+        .into_iter()
+        .filter_map(|(field, new)| {
+            let old = before.get(&field).cloned().unwrap_or(Value::Null);
+            (old != new).then(|| (field, json!({ "old": old, "new": new })))
+        })
+        .collect()
+}
+
+fn fields(snapshot: &ProductSnapshot) -> Map<String, Value> {
+    match serde_json::to_value(snapshot) {
+        Ok(Value::Object(fields)) => fields,
+        _ => unreachable!("a snapshot serializes to an object"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> ProductSnapshot {
+        ProductSnapshot {
+            title: "Lamp".to_string(),
+            content: None,
+            price: Decimal::new(1999, 2),
+            category_ids: vec![1, 3],
+            tags: vec!["outdoor".to_string()],
+            deleted: false,
+        }
+    }
+
+    #[test]
+    fn test_diff_lists_only_changed_fields() {
+        let before = snapshot();
+        let after = ProductSnapshot {
+            title: "Desk lamp".to_string(),
+            tags: vec!["indoor".to_string()],
+            ..snapshot()
+        };
+
+        let changes = diff(Some(&before), &after);
+
+        assert_eq!(
+            Value::Object(changes),
+            json!({
+                "title": { "old": "Lamp", "new": "Desk lamp" },
+                "tags": { "old": ["outdoor"], "new": ["indoor"] },
+            })
+        );
+    }
+
+    #[test]
+    fn test_diff_of_identical_snapshots_is_empty() {
+        assert!(diff(Some(&snapshot()), &snapshot()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_without_before_lists_every_set_field() {
+        let changes = diff(None, &snapshot());
+
+        let mut fields: Vec<&str> = changes.keys().map(String::as_str).collect();
+        fields.sort_unstable();
+
+        assert_eq!(
+            fields,
+            vec!["category_ids", "deleted", "price", "tags", "title"]
+        );
+        assert_eq!(changes["title"], json!({ "old": null, "new": "Lamp" }));
+    }
+}
@@ -1,13 +1,62 @@
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     routing::{get, post},
 };
 
-use super::product_controller::{create_product_handler, find_all_products_handler};
-use crate::state::AppState;
+use super::inventory_controller::{
+    adjust_stock_handler, find_movements_handler, release_stock_handler, reserve_stock_handler,
+};
+use super::product_controller::{
+    create_product_handler, delete_product_handler, find_all_products_handler,
+    find_product_handler, find_trash_handler, restore_product_handler, search_products_handler,
+    update_product_handler,
+};
+use super::product_image_controller::{
+    delete_image_handler, download_image_handler, find_images_handler, max_upload_bytes,
+    upload_images_handler,
+};
+use super::product_revision_controller::{find_history_handler, find_snapshot_handler};
+use super::product_transfer_controller::{
+    MAX_IMPORT_BYTES, export_products_handler, import_products_handler,
+};
+use crate::{config::product::ProductConfig, state::AppState};
 
-pub fn router() -> Router<AppState> {
+pub fn router(config: &ProductConfig) -> Router<AppState> {
     Router::new()
         .route("/", get(find_all_products_handler))
         .route("/", post(create_product_handler))
+        .route("/search", get(search_products_handler))
+        .route(
+            "/import",
+            post(import_products_handler).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+        )
+        .route("/export", get(export_products_handler))
+        .route("/trash", get(find_trash_handler))
+        .route(
+            "/{id}",
+            get(find_product_handler)
+                .patch(update_product_handler)
+                .delete(delete_product_handler),
+        )
+        .route("/{id}/restore", post(restore_product_handler))
+        .route("/{id}/history", get(find_history_handler))
+        .route("/{id}/history/snapshot", get(find_snapshot_handler))
+        .route("/{id}/inventory/movements", get(find_movements_handler))
+        .route("/{id}/inventory/adjust", post(adjust_stock_handler))
+        .route("/{id}/inventory/reserve", post(reserve_stock_handler))
+        .route("/{id}/inventory/release", post(release_stock_handler))
+        .route(
+            "/{id}/images",
+            get(find_images_handler)
+                // Uploads also enforce the per-image limit while streaming.
+                .post(upload_images_handler)
+                .layer(DefaultBodyLimit::max(max_upload_bytes(
+                    config.max_image_bytes,
+                ))),
+        )
+        .route(
+            "/{id}/images/{image_id}",
+            get(download_image_handler).delete(delete_image_handler),
+        )
 }
@@ -1,82 +1,470 @@
+use std::sync::Arc;
+
 use anyhow::Result;
+use chrono::{Duration, NaiveDateTime, Utc};
 use rust_decimal::Decimal;
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction, DbBackend,
+    EntityTrait, FromQueryResult, Order, QueryFilter, QueryOrder, QuerySelect, Select, Set,
+    Statement, TransactionTrait,
+    sea_query::{Expr, Query, Value, extension::postgres::PgExpr},
+};
+use tracing::instrument;
+
+use crate::{
+    modules::{
+        category::{category_service::CategoryService, product_category_entity},
+        shared::pagination::{Cursor, Page, PageCursor, PageDirection, PageRequest},
+        tag::{product_tag_entity, tag_entity, tag_service::TagService},
+        user::user_entity,
+    },
+    storage::Storage,
+};
+
+use super::{
+    product_dto::{
+        MAX_SEARCH_OFFSET, ProductCursor, ProductFilter, ProductSort, ProductSortField,
+        SearchCursor,
+    },
+    product_entity, product_image_entity,
+    product_revision_entity::RevisionAction,
+    product_revision_service::{ProductRevisionService, ProductSnapshot},
+};
+
+const SEARCH_SQL: &str = r#"
+SELECT p.id, p.owner_id, p.title, p.content, p.price, p.stock, p.created_at, p.updated_at,
+    ts_rank(p.search_vector, query) AS rank,
+    ts_headline($1::regconfig, p.title, query,
+        'StartSel=<mark>, StopSel=</mark>, HighlightAll=true') AS title_highlight,
+    CASE WHEN p.content IS NULL THEN NULL ELSE ts_headline($1::regconfig, p.content, query,
+        'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=20, MinWords=5') END AS snippet
+FROM products p, websearch_to_tsquery($1::regconfig, $2) AS query
+WHERE p.search_vector @@ query AND p.deleted_at IS NULL
+ORDER BY rank DESC, p.id DESC
+LIMIT $3 OFFSET $4
+"#;
+
+#[derive(Debug, FromQueryResult)]
+pub struct ProductSearchHit {
+    pub id: i32,
+    pub owner_id: i32,
+    pub title: String,
+    pub content: Option<String>,
+    pub price: Decimal,
+    pub stock: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub rank: f32,
+    pub title_highlight: String,
+    pub snippet: Option<String>,
+}
 
-use crate::modules::user::user_entity;
+/// A validated product ready to insert; `tags` are already normalized.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewProduct {
+    pub title: String,
+    pub content: Option<String>,
+    pub price: Decimal,
+    pub category_ids: Vec<i32>,
+    pub tags: Vec<String>,
+}
+
+/// An update to apply; `None` fields are left unchanged, and `category_ids`
+/// and `tags` replace the product's current sets when given.
+#[derive(Debug, Clone, Default)]
+pub struct ProductChanges {
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub price: Option<Decimal>,
+    pub category_ids: Option<Vec<i32>>,
+    pub tags: Option<Vec<String>>,
+}
 
-use super::product_entity;
+#[derive(Debug, Clone)]
+pub struct ProductExport {
+    pub product: product_entity::Model,
+    pub category_ids: Vec<i32>,
+    pub tags: Vec<String>,
+}
 
 #[derive(Clone)]
 pub struct ProductService;
 
 impl ProductService {
+    #[instrument(skip(db, product))]
     pub async fn create_product(
         db: &DatabaseConnection,
         owner_id: i32,
-        title: String,
-        content: Option<String>,
-        price: Decimal,
+        product: NewProduct,
+    ) -> Result<product_entity::Model> {
+        let txn = db.begin().await?;
+        let inserted = Self::insert_product(&txn, owner_id, product).await?;
+        txn.commit().await?;
+        Ok(inserted)
+    }
+
+    /// Inserts every product in one transaction, so either all or none of
+    /// them are created.
+    #[instrument(skip(db, products), fields(rows = products.len()))]
+    pub async fn import_products(
+        db: &DatabaseConnection,
+        owner_id: i32,
+        products: Vec<NewProduct>,
+    ) -> Result<Vec<product_entity::Model>> {
+        let txn = db.begin().await?;
+
+        let mut inserted = Vec::with_capacity(products.len());
+        for product in products {
+            inserted.push(Self::insert_product(&txn, owner_id, product).await?);
+        }
+
+        txn.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn insert_product(
+        txn: &DatabaseTransaction,
+        owner_id: i32,
+        product: NewProduct,
     ) -> Result<product_entity::Model> {
-        let product = product_entity::ActiveModel {
+        let active_model = product_entity::ActiveModel {
             owner_id: sea_orm::ActiveValue::Set(owner_id),
-            title: sea_orm::ActiveValue::Set(title),
-            content: sea_orm::ActiveValue::Set(content),
-            price: sea_orm::ActiveValue::Set(price),
+            title: sea_orm::ActiveValue::Set(product.title),
+            content: sea_orm::ActiveValue::Set(product.content),
+            price: sea_orm::ActiveValue::Set(product.price),
             ..Default::default()
         };
 
-        let inserted = product.insert(db).await?;
+        let inserted = active_model.insert(txn).await?;
+        CategoryService::set_product_categories(txn, inserted.id, &product.category_ids).await?;
+        TagService::set_product_tags(txn, inserted.id, &product.tags).await?;
+
+        let snapshot = ProductRevisionService::snapshot(txn, &inserted).await?;
+        ProductRevisionService::record(
+            txn,
+            inserted.id,
+            Some(owner_id),
+            RevisionAction::Created,
+            None,
+            &snapshot,
+        )
+        .await?;
+
         Ok(inserted)
     }
 
+    #[instrument(skip(db, changes))]
     pub async fn update_product(
         db: &DatabaseConnection,
         product_id: i32,
-        title: Option<String>,
-        content: Option<String>,
-        price: Option<Decimal>,
+        actor_id: i32,
+        changes: ProductChanges,
     ) -> Result<Option<product_entity::Model>> {
-        if let Some(product) = product_entity::Entity::find_by_id(product_id)
-            .one(db)
+        let txn = db.begin().await?;
+
+        let Some(product) = active_products()
+            .filter(product_entity::Column::Id.eq(product_id))
+            .lock_exclusive()
+            .one(&txn)
             .await?
-        {
-            let mut active_model: product_entity::ActiveModel = product.into();
+        else {
+            return Ok(None);
+        };
 
-            if let Some(t) = title {
-                active_model.title = Set(t);
-            }
-            if let Some(c) = content {
-                active_model.content = Set(Some(c));
-            }
-            if let Some(p) = price {
-                active_model.price = Set(p);
-            }
+        let before = ProductRevisionService::snapshot(&txn, &product).await?;
+        let mut active_model: product_entity::ActiveModel = product.into();
+
+        if let Some(t) = changes.title {
+            active_model.title = Set(t);
+        }
+        if let Some(c) = changes.content {
+            active_model.content = Set(Some(c));
+        }
+        if let Some(p) = changes.price {
+            active_model.price = Set(p);
+        }
+
+        let updated_product = active_model.update(&txn).await?;
 
-            let updated_product = active_model.update(db).await?;
-            Ok(Some(updated_product))
-        } else {
-            Ok(None)
+        if let Some(category_ids) = &changes.category_ids {
+            CategoryService::set_product_categories(&txn, product_id, category_ids).await?;
+        }
+        if let Some(tags) = &changes.tags {
+            TagService::set_product_tags(&txn, product_id, tags).await?;
         }
+
+        let after = ProductRevisionService::snapshot(&txn, &updated_product).await?;
+        ProductRevisionService::record(
+            &txn,
+            product_id,
+            Some(actor_id),
+            RevisionAction::Updated,
+            Some(&before),
+            &after,
+        )
+        .await?;
+
+        txn.commit().await?;
+        Ok(Some(updated_product))
     }
 
-    pub async fn find_all_products(db: &DatabaseConnection) -> Result<Vec<product_entity::Model>> {
-        let products = product_entity::Entity::find().all(db).await?;
-        Ok(products)
+    /// The owner's next `limit` live products after `after_id` in id order,
+    /// with their category ids and tag names. Export walks these batches so
+    /// memory stays flat however many products there are.
+    #[instrument(skip(db))]
+    pub async fn find_export_batch(
+        db: &DatabaseConnection,
+        owner_id: i32,
+        after_id: Option<i32>,
+        limit: u64,
+    ) -> Result<Vec<ProductExport>> {
+        let mut query = active_products().filter(product_entity::Column::OwnerId.eq(owner_id));
+        if let Some(after_id) = after_id {
+            query = query.filter(product_entity::Column::Id.gt(after_id));
+        }
+
+        let products = query
+            .order_by_asc(product_entity::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        let ids: Vec<i32> = products.iter().map(|product| product.id).collect();
+
+        let categories = product_category_entity::Entity::find()
+            .filter(product_category_entity::Column::ProductId.is_in(ids.clone()))
+            .order_by_asc(product_category_entity::Column::CategoryId)
+            .all(db)
+            .await?;
+
+        let tags = product_tag_entity::Entity::find()
+            .filter(product_tag_entity::Column::ProductId.is_in(ids))
+            .find_also_related(tag_entity::Entity)
+            .order_by_asc(tag_entity::Column::Name)
+            .all(db)
+            .await?;
+
+        Ok(products
+            .into_iter()
+            .map(|product| ProductExport {
+                category_ids: categories
+                    .iter()
+                    .filter(|link| link.product_id == product.id)
+                    .map(|link| link.category_id)
+                    .collect(),
+                tags: tags
+                    .iter()
+                    .filter(|(link, _)| link.product_id == product.id)
+                    .filter_map(|(_, tag)| tag.as_ref().map(|tag| tag.name.clone()))
+                    .collect(),
+                product,
+            })
+            .collect())
     }
 
-    pub async fn find_all_products_with_owner(
+    #[instrument(skip_all)]
+    pub async fn find_products_with_owner_page(
         db: &DatabaseConnection,
-    ) -> Result<Vec<(product_entity::Model, Option<user_entity::Model>)>> {
-        let products_with_owners = product_entity::Entity::find()
-            .find_also_related(user_entity::Entity)
+        filter: &ProductFilter,
+        sort: &[ProductSort],
+        page: PageRequest<ProductCursor>,
+    ) -> Result<Page<(product_entity::Model, Option<user_entity::Model>)>> {
+        let keys = sort_keys(sort);
+
+        let products_with_owners = page
+            .apply_keyset(
+                active_products()
+                    .filter(filter_condition(filter))
+                    .find_also_related(user_entity::Entity),
+                &keys,
+                |cursor| cursor_values(cursor, &keys),
+            )
             .all(db)
             .await?;
-        Ok(products_with_owners)
+
+        Ok(page.into_page(products_with_owners, |(product, _)| product.into()))
     }
 
+    /// Full-text search using `websearch_to_tsquery` syntax (quoted phrases,
+    /// `or`, `-exclusions`), best matches first.
+    #[instrument(skip_all)]
+    pub async fn search_products(
+        db: &DatabaseConnection,
+        language: &str,
+        q: &str,
+        page: PageRequest<SearchCursor>,
+    ) -> Result<Page<ProductSearchHit>> {
+        let (offset, limit) = match page.direction {
+            PageDirection::First => (0, page.limit),
+            PageDirection::After(cursor) => (cursor.offset, page.limit),
+            PageDirection::Before(cursor) => (
+                cursor.offset.saturating_sub(page.limit),
+                page.limit.min(cursor.offset),
+            ),
+        };
+
+        let mut hits = ProductSearchHit::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            SEARCH_SQL,
+            [
+                language.into(),
+                q.into(),
+                (i64::try_from(limit)? + 1).into(),
+                i64::try_from(offset)?.into(),
+            ],
+        ))
+        .all(db)
+        .await?;
+
+        let has_more = hits.len() as u64 > limit;
+        hits.truncate(limit as usize);
+
+        let (next_cursor, prev_cursor) =
+            search_cursors(&page.direction, offset, hits.len(), has_more);
+
+        Ok(Page {
+            data: hits,
+            next_cursor: next_cursor.map(|cursor| cursor.encode()),
+            prev_cursor: prev_cursor.map(|cursor| cursor.encode()),
+        })
+    }
+
+    #[instrument(skip(db))]
     pub async fn find_product_by_id(
         db: &DatabaseConnection,
         product_id: i32,
+    ) -> Result<Option<product_entity::Model>> {
+        let product = active_products()
+            .filter(product_entity::Column::Id.eq(product_id))
+            .one(db)
+            .await?;
+        Ok(product)
+    }
+
+    #[instrument(skip(db))]
+    pub async fn find_deleted_product_by_id(
+        db: &DatabaseConnection,
+        product_id: i32,
+    ) -> Result<Option<product_entity::Model>> {
+        let product = product_entity::Entity::find_by_id(product_id)
+            .filter(product_entity::Column::DeletedAt.is_not_null())
+            .one(db)
+            .await?;
+        Ok(product)
+    }
+
+    /// The owner's trash, most recently deleted first.
+    #[instrument(skip(db, page))]
+    pub async fn find_deleted_products_page(
+        db: &DatabaseConnection,
+        owner_id: i32,
+        page: PageRequest,
+    ) -> Result<Page<product_entity::Model>> {
+        let products = page
+            .apply(
+                product_entity::Entity::find()
+                    .filter(product_entity::Column::OwnerId.eq(owner_id))
+                    .filter(product_entity::Column::DeletedAt.is_not_null()),
+                product_entity::Column::DeletedAt,
+                product_entity::Column::Id,
+            )
+            .all(db)
+            .await?;
+
+        Ok(page.into_page(products, |product| Cursor {
+            created_at: product.deleted_at.unwrap_or(product.created_at),
+            id: product.id,
+        }))
+    }
+
+    /// Moves the product to the trash; it stays restorable until purged.
+    #[instrument(skip(db))]
+    pub async fn delete_product(
+        db: &DatabaseConnection,
+        product_id: i32,
+        actor_id: i32,
+    ) -> Result<bool> {
+        let txn = db.begin().await?;
+
+        let deleted = product_entity::Entity::update_many()
+            .col_expr(
+                product_entity::Column::DeletedAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(product_entity::Column::Id.eq(product_id))
+            .filter(product_entity::Column::DeletedAt.is_null())
+            .exec_with_returning(&txn)
+            .await?;
+
+        let Some(product) = deleted.first() else {
+            return Ok(false);
+        };
+
+        Self::record_trash_change(&txn, product, actor_id, RevisionAction::Deleted).await?;
+
+        txn.commit().await?;
+        Ok(true)
+    }
+
+    #[instrument(skip(db))]
+    pub async fn restore_product(
+        db: &DatabaseConnection,
+        product_id: i32,
+        actor_id: i32,
+    ) -> Result<Option<product_entity::Model>> {
+        let txn = db.begin().await?;
+
+        let restored = product_entity::Entity::update_many()
+            .col_expr(
+                product_entity::Column::DeletedAt,
+                Expr::value(Option::<NaiveDateTime>::None),
+            )
+            .filter(product_entity::Column::Id.eq(product_id))
+            .filter(product_entity::Column::DeletedAt.is_not_null())
+            .exec_with_returning(&txn)
+            .await?;
+
+        let Some(product) = restored.into_iter().next() else {
+            return Ok(None);
+        };
+
+        Self::record_trash_change(&txn, &product, actor_id, RevisionAction::Restored).await?;
+
+        txn.commit().await?;
+        Ok(Some(product))
+    }
+
+    /// Records a move into or out of the trash, where only `deleted` changes.
+    async fn record_trash_change(
+        txn: &DatabaseTransaction,
+        product: &product_entity::Model,
+        actor_id: i32,
+        action: RevisionAction,
+    ) -> Result<()> {
+        let after = ProductRevisionService::snapshot(txn, product).await?;
+        let before = ProductSnapshot {
+            deleted: !after.deleted,
+            ..after.clone()
+        };
+
+        ProductRevisionService::record(
+            txn,
+            product.id,
+            Some(actor_id),
+            action,
+            Some(&before),
+            &after,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// The product whether or not it is in the trash.
+    #[instrument(skip(db))]
+    pub async fn find_product_including_deleted(
+        db: &DatabaseConnection,
+        product_id: i32,
     ) -> Result<Option<product_entity::Model>> {
         let product = product_entity::Entity::find_by_id(product_id)
             .one(db)
@@ -84,10 +472,295 @@ impl ProductService {
         Ok(product)
     }
 
-    pub async fn delete_product(db: &DatabaseConnection, product_id: i32) -> Result<bool> {
-        let result = product_entity::Entity::delete_by_id(product_id)
-            .exec(db)
+    /// Permanently deletes products trashed more than `retention_days` ago,
+    /// along with their stored images. The products are locked while they
+    /// are purged, so one restored concurrently either survives with its
+    /// images or is purged with them.
+    #[instrument(skip(db, storage))]
+    pub async fn purge_deleted_products(
+        db: &DatabaseConnection,
+        storage: &dyn Storage,
+        retention_days: i64,
+    ) -> Result<u64> {
+        let cutoff = Utc::now().naive_utc() - Duration::days(retention_days);
+        let txn = db.begin().await?;
+
+        let product_ids: Vec<i32> = product_entity::Entity::find()
+            .select_only()
+            .column(product_entity::Column::Id)
+            .filter(product_entity::Column::DeletedAt.lt(cutoff))
+            .lock_exclusive()
+            .into_tuple()
+            .all(&txn)
+            .await?;
+        if product_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let image_keys: Vec<String> = product_image_entity::Entity::find()
+            .select_only()
+            .column(product_image_entity::Column::StorageKey)
+            .filter(product_image_entity::Column::ProductId.is_in(product_ids.clone()))
+            .into_tuple()
+            .all(&txn)
             .await?;
-        Ok(result.rows_affected > 0)
+
+        let result = product_entity::Entity::delete_many()
+            .filter(product_entity::Column::Id.is_in(product_ids))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+
+        for key in image_keys {
+            if let Err(e) = storage.delete(&key).await {
+                tracing::warn!("Failed to remove image {key}: {e}");
+            }
+        }
+
+        Ok(result.rows_affected)
+    }
+
+    pub fn spawn_trash_purge(
+        db: DatabaseConnection,
+        storage: Arc<dyn Storage>,
+        retention_days: i64,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match Self::purge_deleted_products(&db, storage.as_ref(), retention_days).await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!("Purged {purged} deleted products"),
+                    Err(e) => tracing::error!("Failed to purge deleted products: {e}"),
+                }
+            }
+        })
+    }
+}
+
+fn active_products() -> Select<product_entity::Entity> {
+    product_entity::Entity::find().filter(product_entity::Column::DeletedAt.is_null())
+}
+
+fn filter_condition(filter: &ProductFilter) -> Condition {
+    let mut condition = Condition::all();
+
+    if let Some(min_price) = filter.min_price {
+        condition = condition.add(product_entity::Column::Price.gte(min_price));
+    }
+    if let Some(max_price) = filter.max_price {
+        condition = condition.add(product_entity::Column::Price.lte(max_price));
+    }
+    if let Some(owner_id) = filter.owner_id {
+        condition = condition.add(product_entity::Column::OwnerId.eq(owner_id));
+    }
+    if let Some(created_after) = filter.created_after {
+        condition = condition.add(product_entity::Column::CreatedAt.gte(created_after));
+    }
+    if let Some(created_before) = filter.created_before {
+        condition = condition.add(product_entity::Column::CreatedAt.lt(created_before));
+    }
+    if let Some(q) = &filter.q {
+        let pattern = format!("%{}%", escape_like(q));
+        condition = condition.add(
+            Condition::any()
+                .add(
+                    Expr::col((product_entity::Entity, product_entity::Column::Title))
+                        .ilike(pattern.clone()),
+                )
+                .add(
+                    Expr::col((product_entity::Entity, product_entity::Column::Content))
+                        .ilike(pattern),
+                ),
+        );
+    }
+    if let Some(category_id) = filter.category_id {
+        condition = condition.add(
+            product_entity::Column::Id.in_subquery(
+                Query::select()
+                    .column(product_category_entity::Column::ProductId)
+                    .from(product_category_entity::Entity)
+                    .and_where(product_category_entity::Column::CategoryId.eq(category_id))
+                    .to_owned(),
+            ),
+        );
+    }
+    if let Some(tag) = &filter.tag {
+        condition = condition.add(
+            product_entity::Column::Id.in_subquery(
+                Query::select()
+                    .column((
+                        product_tag_entity::Entity,
+                        product_tag_entity::Column::ProductId,
+                    ))
+                    .from(product_tag_entity::Entity)
+                    .inner_join(
+                        tag_entity::Entity,
+                        Expr::col((tag_entity::Entity, tag_entity::Column::Id)).equals((
+                            product_tag_entity::Entity,
+                            product_tag_entity::Column::TagId,
+                        )),
+                    )
+                    .and_where(tag_entity::Column::Name.eq(tag.as_str()))
+                    .to_owned(),
+            ),
+        );
+    }
+
+    condition
+}
+
+/// Postgres treats `\` as the default LIKE escape character.
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The requested sort, completed with `created_at` and `id` so the ordering
+/// is total and keyset pagination never skips or repeats rows.
+fn sort_keys(sort: &[ProductSort]) -> Vec<(product_entity::Column, Order)> {
+    let order = |descending: bool| if descending { Order::Desc } else { Order::Asc };
+
+    let mut keys: Vec<(product_entity::Column, Order)> = sort
+        .iter()
+        .map(|key| {
+            let column = match key.field {
+                ProductSortField::Price => product_entity::Column::Price,
+                ProductSortField::CreatedAt => product_entity::Column::CreatedAt,
+            };
+            (column, order(key.descending))
+        })
+        .collect();
+
+    if !sort
+        .iter()
+        .any(|key| key.field == ProductSortField::CreatedAt)
+    {
+        keys.push((product_entity::Column::CreatedAt, Order::Desc));
+    }
+    keys.push((product_entity::Column::Id, Order::Desc));
+
+    keys
+}
+
+/// Cursors around a page of `returned` hits starting at `offset`. No next
+/// cursor is issued past `MAX_SEARCH_OFFSET`, since decoding would reject it.
+fn search_cursors(
+    direction: &PageDirection<SearchCursor>,
+    offset: u64,
+    returned: usize,
+    has_more: bool,
+) -> (Option<SearchCursor>, Option<SearchCursor>) {
+    let next_offset = offset + returned as u64;
+    let next_cursor = ((has_more || matches!(direction, PageDirection::Before(_)))
+        && next_offset <= MAX_SEARCH_OFFSET)
+        .then_some(SearchCursor {
+            offset: next_offset,
+        });
+    let prev_cursor = (offset > 0).then_some(SearchCursor { offset });
+
+    (next_cursor, prev_cursor)
+}
+
+fn cursor_values(cursor: &ProductCursor, keys: &[(product_entity::Column, Order)]) -> Vec<Value> {
+    keys.iter()
+        .map(|(column, _)| match column {
+            product_entity::Column::Price => cursor.price.into(),
+            product_entity::Column::CreatedAt => cursor.created_at.into(),
+            _ => cursor.id.into(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DbBackend, IdenStatic, QueryTrait};
+
+    use super::*;
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like(r"50%_off\"), r"50\%\_off\\");
+    }
+
+    #[test]
+    fn test_filter_condition_builds_where_clause() {
+        let filter = ProductFilter {
+            min_price: Some(Decimal::new(5, 0)),
+            owner_id: Some(2),
+            q: Some("lamp".to_string()),
+            ..Default::default()
+        };
+
+        let sql = product_entity::Entity::find()
+            .filter(filter_condition(&filter))
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(
+            sql.ends_with(
+                r#"WHERE "products"."price" >= 5 AND "products"."owner_id" = 2 AND (("products"."title" ILIKE '%lamp%') OR ("products"."content" ILIKE '%lamp%'))"#
+            ),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn test_filter_condition_by_category_and_tag() {
+        let filter = ProductFilter {
+            category_id: Some(4),
+            tag: Some("outdoor".to_string()),
+            ..Default::default()
+        };
+
+        let sql = product_entity::Entity::find()
+            .filter(filter_condition(&filter))
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(
+            sql.ends_with(
+                r#"WHERE "products"."id" IN (SELECT "product_id" FROM "product_categories" WHERE "product_categories"."category_id" = 4) AND "products"."id" IN (SELECT "product_tags"."product_id" FROM "product_tags" INNER JOIN "tags" ON "tags"."id" = "product_tags"."tag_id" WHERE "tags"."name" = 'outdoor')"#
+            ),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn test_search_cursors_stop_at_the_offset_cap() {
+        let direction = PageDirection::After(SearchCursor { offset: 9_980 });
+        let (next, prev) = search_cursors(&direction, 9_980, 20, true);
+        let next = next.expect("a page ending on the cap still links onward");
+        assert_eq!(next.offset, MAX_SEARCH_OFFSET);
+        assert_eq!(SearchCursor::decode(&next.encode()), Some(next));
+        assert_eq!(prev, Some(SearchCursor { offset: 9_980 }));
+
+        let direction = PageDirection::After(next);
+        let (next, prev) = search_cursors(&direction, MAX_SEARCH_OFFSET, 20, true);
+        assert_eq!(next, None);
+        assert!(SearchCursor::decode(&prev.unwrap().encode()).is_some());
+    }
+
+    #[test]
+    fn test_sort_keys_always_end_with_id() {
+        let keys = sort_keys(&[ProductSort {
+            field: ProductSortField::Price,
+            descending: false,
+        }]);
+
+        let columns: Vec<String> = keys
+            .iter()
+            .map(|(column, _)| column.as_str().to_string())
+            .collect();
+
+        assert_eq!(columns, vec!["price", "created_at", "id"]);
     }
 }
@@ -0,0 +1,440 @@
+//! CSV and NDJSON encodings for bulk product import and export. Both formats
+//! use the same columns, so an export can be edited and imported again.
+
+use csv::{ReaderBuilder, StringRecord, Trim, WriterBuilder};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationErrors};
+
+use super::{
+    product_dto::{CreateProductPayload, price_to_decimal},
+    product_service::{NewProduct, ProductExport},
+};
+use crate::modules::{shared::pagination::invalid, tag::tag_service::normalize_tag_names};
+
+/// Separates multiple tags or category ids inside one CSV cell.
+const LIST_SEPARATOR: char = ';';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    Csv,
+    Ndjson,
+}
+
+impl DataFormat {
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim();
+        match mime.to_ascii_lowercase().as_str() {
+            "text/csv" => Some(DataFormat::Csv),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => {
+                Some(DataFormat::Ndjson)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            DataFormat::Csv => "text/csv; charset=utf-8",
+            DataFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            DataFormat::Csv => "csv",
+            DataFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// `Atomic` imports nothing if any row is invalid; `SkipInvalid` imports the
+/// valid rows and reports the rest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    #[default]
+    Atomic,
+    SkipInvalid,
+}
+
+/// One input row; `line` is where it starts in the uploaded file.
+#[derive(Debug)]
+pub struct ImportRow {
+    pub line: u64,
+    pub product: Result<NewProduct, ValidationErrors>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CsvProductRow {
+    title: String,
+    #[serde(default)]
+    content: Option<String>,
+    price: f64,
+    #[serde(default)]
+    category_ids: Option<String>,
+    #[serde(default)]
+    tags: Option<String>,
+}
+
+/// Parses and validates every row with the same rules as
+/// `CreateProductPayload`. Category ids are only checked for existence later,
+/// against the database.
+pub fn parse_rows(format: DataFormat, body: &[u8]) -> Vec<ImportRow> {
+    match format {
+        DataFormat::Csv => parse_csv(body),
+        DataFormat::Ndjson => parse_ndjson(body),
+    }
+}
+
+fn parse_csv(body: &[u8]) -> Vec<ImportRow> {
+    let mut reader = ReaderBuilder::new().trim(Trim::All).from_reader(body);
+
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            return vec![ImportRow {
+                line: 1,
+                product: Err(parse_error(e.to_string())),
+            }];
+        }
+    };
+
+    reader
+        .records()
+        .map(|record| match record {
+            Ok(record) => ImportRow {
+                line: record.position().map_or(0, |position| position.line()),
+                product: csv_payload(&record, &headers).and_then(into_new_product),
+            },
+            Err(e) => ImportRow {
+                line: e.position().map_or(0, |position| position.line()),
+                product: Err(parse_error(e.to_string())),
+            },
+        })
+        .collect()
+}
+
+fn csv_payload(
+    record: &StringRecord,
+    headers: &StringRecord,
+) -> Result<CreateProductPayload, ValidationErrors> {
+    let row: CsvProductRow = record
+        .deserialize(Some(headers))
+        .map_err(|e| parse_error(e.to_string()))?;
+
+    let category_ids = match row.category_ids.as_deref() {
+        Some(ids) => Some(
+            split_list(ids)
+                .map(str::parse)
+                .collect::<Result<Vec<i32>, _>>()
+                .map_err(|_| {
+                    let mut errors = ValidationErrors::new();
+                    errors.add(
+                        "category_ids",
+                        invalid(
+                            "type",
+                            format!("Expected integers separated by `{LIST_SEPARATOR}`"),
+                        ),
+                    );
+                    errors
+                })?,
+        ),
+        None => None,
+    };
+
+    Ok(CreateProductPayload {
+        title: row.title,
+        content: row.content,
+        price: row.price,
+        category_ids,
+        tags: row
+            .tags
+            .as_deref()
+            .map(|tags| split_list(tags).map(str::to_string).collect()),
+    })
+}
+
+fn parse_ndjson(body: &[u8]) -> Vec<ImportRow> {
+    body.split(|&byte| byte == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.trim_ascii().is_empty())
+        .map(|(index, line)| ImportRow {
+            line: index as u64 + 1,
+            product: serde_json::from_slice::<CreateProductPayload>(line)
+                .map_err(|e| parse_error(e.to_string()))
+                .and_then(into_new_product),
+        })
+        .collect()
+}
+
+fn into_new_product(payload: CreateProductPayload) -> Result<NewProduct, ValidationErrors> {
+    payload.validate()?;
+
+    let mut category_ids = payload.category_ids.unwrap_or_default();
+    category_ids.sort_unstable();
+    category_ids.dedup();
+
+    Ok(NewProduct {
+        price: price_to_decimal(payload.price)?,
+        title: payload.title,
+        content: payload.content,
+        category_ids,
+        // Validation has already checked every tag name.
+        tags: payload
+            .tags
+            .as_deref()
+            .and_then(normalize_tag_names)
+            .unwrap_or_default(),
+    })
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(LIST_SEPARATOR)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+fn parse_error(message: String) -> ValidationErrors {
+    let mut errors = ValidationErrors::new();
+    errors.add("row", invalid("parse", message));
+    errors
+}
+
+#[derive(Serialize)]
+struct CsvExportRow<'a> {
+    id: i32,
+    title: &'a str,
+    content: Option<&'a str>,
+    price: String,
+    stock: i32,
+    category_ids: String,
+    tags: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Serialize)]
+struct NdjsonExportRow<'a> {
+    id: i32,
+    title: &'a str,
+    content: Option<&'a str>,
+    price: f64,
+    stock: i32,
+    category_ids: &'a [i32],
+    tags: &'a [String],
+    created_at: String,
+    updated_at: String,
+}
+
+/// Encodes one export batch; only the first batch of a CSV export carries
+/// the header row.
+pub fn encode_batch(
+    format: DataFormat,
+    batch: &[ProductExport],
+    with_header: bool,
+) -> anyhow::Result<Vec<u8>> {
+    match format {
+        DataFormat::Csv => {
+            let mut writer = WriterBuilder::new()
+                .has_headers(with_header)
+                .from_writer(Vec::new());
+
+            if with_header && batch.is_empty() {
+                writer.write_record([
+                    "id",
+                    "title",
+                    "content",
+                    "price",
+                    "stock",
+                    "category_ids",
+                    "tags",
+                    "created_at",
+                    "updated_at",
+                ])?;
+            }
+
+            for export in batch {
+                let product = &export.product;
+                writer.serialize(CsvExportRow {
+                    id: product.id,
+                    title: &product.title,
+                    content: product.content.as_deref(),
+                    price: product.price.to_string(),
+                    stock: product.stock,
+                    category_ids: export
+                        .category_ids
+                        .iter()
+                        .map(i32::to_string)
+                        .collect::<Vec<_>>()
+                        .join(&LIST_SEPARATOR.to_string()),
+                    tags: export.tags.join(&LIST_SEPARATOR.to_string()),
+                    created_at: product.created_at.to_string(),
+                    updated_at: product.updated_at.to_string(),
+                })?;
+            }
+
+            Ok(writer.into_inner()?)
+        }
+        DataFormat::Ndjson => {
+            let mut out = Vec::new();
+
+            for export in batch {
+                let product = &export.product;
+                serde_json::to_writer(
+                    &mut out,
+                    &NdjsonExportRow {
+                        id: product.id,
+                        title: &product.title,
+                        content: product.content.as_deref(),
+                        price: product.price.to_f64().unwrap_or_default(),
+                        stock: product.stock,
+                        category_ids: &export.category_ids,
+                        tags: &export.tags,
+                        created_at: product.created_at.to_string(),
+                        updated_at: product.updated_at.to_string(),
+                    },
+                )?;
+                out.push(b'\n');
+            }
+
+            Ok(out)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::modules::product::product_entity;
+
+    fn error_fields(row: &ImportRow) -> Vec<String> {
+        let mut fields: Vec<String> = row
+            .product
+            .as_ref()
+            .unwrap_err()
+            .errors()
+            .keys()
+            .map(|field| field.to_string())
+            .collect();
+        fields.sort();
+        fields
+    }
+
+    #[test]
+    fn test_data_format_from_content_type() {
+        assert_eq!(
+            DataFormat::from_content_type("text/csv; charset=utf-8"),
+            Some(DataFormat::Csv)
+        );
+        assert_eq!(
+            DataFormat::from_content_type("application/x-ndjson"),
+            Some(DataFormat::Ndjson)
+        );
+        assert_eq!(DataFormat::from_content_type("application/json"), None);
+    }
+
+    #[test]
+    fn test_parse_csv_rows() {
+        let body = b"title,content,price,category_ids,tags\n\
+            Lamp,,19.99,2;1;2,Outdoor; Lighting\n\
+            ,Nameless,5,,\n\
+            Chair,Oak,-3,,\n\
+            Table,,cheap,,\n\
+            Shelf,,10,x,\n";
+
+        let rows = parse_rows(DataFormat::Csv, body);
+
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(
+            rows[0].product.as_ref().unwrap(),
+            &NewProduct {
+                title: "Lamp".to_string(),
+                content: None,
+                price: Decimal::new(1999, 2),
+                category_ids: vec![1, 2],
+                tags: vec!["outdoor".to_string(), "lighting".to_string()],
+            }
+        );
+        assert_eq!(error_fields(&rows[1]), vec!["title"]);
+        assert_eq!(error_fields(&rows[2]), vec!["price"]);
+        assert_eq!(error_fields(&rows[3]), vec!["row"]);
+        assert_eq!(error_fields(&rows[4]), vec!["category_ids"]);
+        assert_eq!(rows[4].line, 6);
+    }
+
+    #[test]
+    fn test_parse_ndjson_rows() {
+        let body = b"{\"title\":\"Lamp\",\"price\":19.99,\"tags\":[\"Outdoor\"]}\n\
+            \n\
+            {\"title\":\"Lamp\"\n\
+            {\"title\":\"Chair\",\"price\":5,\"tags\":[\"\"]}\n";
+
+        let rows = parse_rows(DataFormat::Ndjson, body);
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].product.as_ref().unwrap().tags, vec!["outdoor"]);
+        assert_eq!(rows[1].line, 3);
+        assert_eq!(error_fields(&rows[1]), vec!["row"]);
+        assert_eq!(rows[2].line, 4);
+        assert_eq!(error_fields(&rows[2]), vec!["tags"]);
+    }
+
+    #[test]
+    fn test_export_round_trips_through_import() {
+        let created_at = DateTime::from_timestamp(1_700_000_000, 0)
+            .unwrap()
+            .naive_utc();
+        let batch = vec![ProductExport {
+            product: product_entity::Model {
+                id: 7,
+                owner_id: 1,
+                title: "Lamp, large".to_string(),
+                content: Some("Bright".to_string()),
+                price: Decimal::new(1999, 2),
+                stock: 3,
+                created_at,
+                updated_at: created_at,
+                deleted_at: None,
+            },
+            category_ids: vec![1, 2],
+            tags: vec!["lighting".to_string(), "outdoor".to_string()],
+        }];
+
+        for format in [DataFormat::Csv, DataFormat::Ndjson] {
+            let encoded = encode_batch(format, &batch, true).unwrap();
+            let rows = parse_rows(format, &encoded);
+
+            assert_eq!(rows.len(), 1, "{format:?}");
+            assert_eq!(
+                rows[0].product.as_ref().unwrap(),
+                &NewProduct {
+                    title: "Lamp, large".to_string(),
+                    content: Some("Bright".to_string()),
+                    price: Decimal::new(1999, 2),
+                    category_ids: vec![1, 2],
+                    tags: vec!["lighting".to_string(), "outdoor".to_string()],
+                },
+                "{format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_csv_export_still_has_header() {
+        let encoded = encode_batch(DataFormat::Csv, &[], true).unwrap();
+
+        assert!(String::from_utf8(encoded).unwrap().starts_with("id,title,"));
+        assert!(
+            encode_batch(DataFormat::Csv, &[], false)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use futures_util::stream;
+use validator::ValidationErrors;
+
+use super::{
+    product_controller::ensure_can_create_products,
+    product_dto::{ExportQuery, ImportQuery, ImportReport, ImportRowError, ImportedRow},
+    product_service::ProductService,
+    product_transfer::{DataFormat, ImportMode, encode_batch, parse_rows},
+};
+use crate::{
+    middleware::{ProductsRead, ProductsWrite, RequireScope},
+    modules::{
+        category::category_service::CategoryService,
+        shared::{error::AppError, pagination::invalid},
+    },
+    state::AppState,
+};
+
+pub const MAX_IMPORT_ROWS: usize = 10_000;
+pub const MAX_IMPORT_BYTES: usize = 10 * 1024 * 1024;
+const EXPORT_BATCH_SIZE: u64 = 500;
+
+pub async fn import_products_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsWrite>,
+    Query(query): Query<ImportQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ImportReport>), AppError> {
+    ensure_can_create_products(&state, &claims).await?;
+
+    let format = query
+        .format
+        .or_else(|| {
+            headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(DataFormat::from_content_type)
+        })
+        .ok_or_else(|| {
+            AppError::invalid_field(
+                "format",
+                "required",
+                "Send text/csv or application/x-ndjson, or pass `format`",
+            )
+        })?;
+
+    let rows = parse_rows(format, &body);
+
+    if rows.is_empty() {
+        return Err(AppError::invalid_field(
+            "body",
+            "required",
+            "The file contains no rows",
+        ));
+    }
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(AppError::invalid_field(
+            "body",
+            "rows",
+            format!("At most {MAX_IMPORT_ROWS} rows can be imported at once"),
+        ));
+    }
+
+    let referenced: Vec<i32> = rows
+        .iter()
+        .filter_map(|row| row.product.as_ref().ok())
+        .flat_map(|product| product.category_ids.iter().copied())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let missing: HashSet<i32> = CategoryService::find_missing_ids(&state.db, &referenced)
+        .await
+        .map_err(AppError::internal)?
+        .into_iter()
+        .collect();
+
+    let total_rows = rows.len();
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+
+    for row in rows {
+        match row.product {
+            Ok(product) if product.category_ids.iter().any(|id| missing.contains(id)) => {
+                let mut row_errors = ValidationErrors::new();
+                row_errors.add(
+                    "category_ids",
+                    invalid("unknown", "Unknown category id".to_string()),
+                );
+                errors.push(ImportRowError {
+                    line: row.line,
+                    errors: row_errors,
+                });
+            }
+            Ok(product) => valid.push((row.line, product)),
+            Err(row_errors) => errors.push(ImportRowError {
+                line: row.line,
+                errors: row_errors,
+            }),
+        }
+    }
+
+    if (query.mode == ImportMode::Atomic && !errors.is_empty()) || valid.is_empty() {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ImportReport {
+                mode: query.mode,
+                total_rows,
+                imported: 0,
+                failed: errors.len(),
+                created: Vec::new(),
+                errors,
+            }),
+        ));
+    }
+
+    let (lines, products): (Vec<u64>, Vec<_>) = valid.into_iter().unzip();
+
+    let inserted = ProductService::import_products(&state.db, claims.sub, products)
+        .await
+        .map_err(AppError::internal)?;
+
+    let created: Vec<ImportedRow> = lines
+        .into_iter()
+        .zip(inserted)
+        .map(|(line, product)| ImportedRow {
+            line,
+            id: product.id,
+        })
+        .collect();
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ImportReport {
+            mode: query.mode,
+            total_rows,
+            imported: created.len(),
+            failed: errors.len(),
+            created,
+            errors,
+        }),
+    ))
+}
+
+/// Streams the caller's products in batches, so large catalogs are never
+/// held in memory at once.
+pub async fn export_products_handler(
+    State(state): State<AppState>,
+    RequireScope(claims, _): RequireScope<ProductsRead>,
+    Query(query): Query<ExportQuery>,
+) -> Response {
+    let format = query.format;
+    let owner_id = claims.sub;
+
+    // `None` ends the stream; otherwise the last exported id and whether the
+    // next chunk is the first.
+    let batches = stream::unfold(Some((None, true)), move |cursor| {
+        let db = state.db.clone();
+        async move {
+            let (after_id, first) = cursor?;
+
+            let batch =
+                match ProductService::find_export_batch(&db, owner_id, after_id, EXPORT_BATCH_SIZE)
+                    .await
+                {
+                    Ok(batch) => batch,
+                    Err(e) => {
+                        tracing::error!("Failed to export products for user {owner_id}: {e}");
+                        return Some((Err(e), None));
+                    }
+                };
+
+            if batch.is_empty() && !first {
+                return None;
+            }
+
+            let next = (batch.len() as u64 == EXPORT_BATCH_SIZE)
+                .then(|| (batch.last().map(|export| export.product.id), false));
+
+            let chunk = encode_batch(format, &batch, first).map(Bytes::from);
+            Some((chunk, next))
+        }
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"products.{}\"", format.extension()),
+            ),
+        ],
+        Body::from_stream(batches),
+    )
+        .into_response()
+}
@@ -1,11 +1,19 @@
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    http::{HeaderValue, StatusCode, header},
+    response::IntoResponse,
+};
 use serde::Serialize;
-use validator::ValidationErrors;
+use validator::{ValidationError, ValidationErrors};
+
+use crate::utils::{metrics, request_id};
 
 #[derive(Serialize)]
 pub struct ErrorResponse<T: Serialize = serde_json::Value> {
     pub message: String,
     pub details: Option<T>,
+    /// Quote this when reporting a problem; it matches the server logs.
+    pub request_id: Option<String>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -25,6 +33,9 @@ pub enum AppError {
     #[error("Forbidden")]
     Forbidden(String),
 
+    #[error("Too many requests")]
+    TooManyRequests { message: String, retry_after: u64 },
+
     #[error("Internal server error")]
     Internal(anyhow::Error),
 }
@@ -34,66 +45,95 @@ impl AppError {
         AppError::Validation(errs)
     }
 
+    /// A validation error on a single field, for checks `Validate` cannot express.
+    pub fn invalid_field(
+        field: &'static str,
+        code: &'static str,
+        message: impl Into<String>,
+    ) -> Self {
+        let mut errs = ValidationErrors::new();
+        errs.add(
+            field,
+            ValidationError::new(code).with_message(message.into().into()),
+        );
+        AppError::Validation(errs)
+    }
+
     pub fn internal<E: Into<anyhow::Error>>(e: E) -> Self {
         AppError::Internal(e.into())
     }
 
-    fn to_response(&self) -> (StatusCode, Json<ErrorResponse>) {
+    /// The variant name, as used for the `app_errors_total` metric.
+    pub fn kind(&self) -> &'static str {
         match self {
+            AppError::Validation(_) => "validation",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::TooManyRequests { .. } => "too_many_requests",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    fn to_response(&self) -> (StatusCode, Json<ErrorResponse>) {
+        let (status, message, details) = match self {
             AppError::Validation(errs) => {
                 let details = serde_json::to_value(errs).unwrap_or(
                     serde_json::json!({"error": "Failed to serialize validation errors"}),
                 );
                 (
                     StatusCode::UNPROCESSABLE_ENTITY,
-                    Json(ErrorResponse {
-                        message: "Payload validation failed".to_string(),
-                        details: Some(details),
-                    }),
+                    "Payload validation failed".to_string(),
+                    Some(details),
                 )
             }
-            AppError::NotFound(msg) => (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    message: msg.clone(),
-                    details: None,
-                }),
-            ),
-            AppError::Conflict(msg) => (
-                StatusCode::CONFLICT,
-                Json(ErrorResponse {
-                    message: msg.clone(),
-                    details: None,
-                }),
-            ),
-            AppError::Unauthorized(msg) => (
-                StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    message: msg.clone(),
-                    details: None,
-                }),
-            ),
-            AppError::Forbidden(msg) => (
-                StatusCode::FORBIDDEN,
-                Json(ErrorResponse {
-                    message: msg.clone(),
-                    details: None,
-                }),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone(), None),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone(), None),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.clone(), None),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone(), None),
+            AppError::TooManyRequests {
+                message,
+                retry_after,
+            } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                message.clone(),
+                Some(serde_json::json!({"retry_after": retry_after})),
             ),
             AppError::Internal(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    message: "Internal server error".to_string(),
-                    details: Some(serde_json::json!({"error": e.to_string()})),
-                }),
+                "Internal server error".to_string(),
+                Some(serde_json::json!({"error": e.to_string()})),
             ),
-        }
+        };
+
+        (
+            status,
+            Json(ErrorResponse {
+                message,
+                details,
+                request_id: request_id::current(),
+            }),
+        )
     }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
+        metrics::record_app_error(self.kind());
+        if let AppError::Internal(e) = &self {
+            tracing::error!(error = format!("{e:#}"), "internal error");
+        }
+
         let (status, json) = self.to_response();
-        (status, json).into_response()
+        let mut response = (status, json).into_response();
+
+        if let AppError::TooManyRequests { retry_after, .. } = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+
+        response
     }
 }
@@ -0,0 +1,383 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, NaiveDateTime};
+use sea_orm::{
+    ColumnTrait, Condition, Order, QueryFilter, QueryOrder, QuerySelect, sea_query::Value,
+};
+use serde::{Deserialize, Serialize};
+use validator::{ValidationError, ValidationErrors};
+
+use super::error::AppError;
+
+pub const DEFAULT_PAGE_LIMIT: u64 = 20;
+pub const MAX_PAGE_LIMIT: u64 = 100;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<u64>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+}
+
+pub trait PageCursor: Sized {
+    fn encode(&self) -> String;
+    fn decode(value: &str) -> Option<Self>;
+}
+
+/// Position of a row in a `(timestamp DESC, id DESC)` ordering; the
+/// timestamp is `created_at` unless a listing says otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub created_at: NaiveDateTime,
+    pub id: i32,
+}
+
+impl PageCursor for Cursor {
+    fn encode(&self) -> String {
+        encode_parts(&[
+            self.created_at.and_utc().timestamp_micros().to_string(),
+            self.id.to_string(),
+        ])
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let [created_at, id] = decode_parts(value)?;
+
+        Some(Cursor {
+            created_at: parse_micros(&created_at)?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// Joins cursor fields into an opaque token. Helpers for `PageCursor` impls.
+pub fn encode_parts(parts: &[String]) -> String {
+    URL_SAFE_NO_PAD.encode(parts.join(":"))
+}
+
+pub fn decode_parts<const N: usize>(value: &str) -> Option<[String; N]> {
+    let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(value).ok()?).ok()?;
+    let parts: Vec<String> = raw.split(':').map(str::to_string).collect();
+    parts.try_into().ok()
+}
+
+pub fn parse_micros(value: &str) -> Option<NaiveDateTime> {
+    Some(DateTime::from_timestamp_micros(value.parse().ok()?)?.naive_utc())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageDirection<C = Cursor> {
+    First,
+    After(C),
+    Before(C),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest<C = Cursor> {
+    pub limit: u64,
+    pub direction: PageDirection<C>,
+}
+
+impl<C: PageCursor> TryFrom<PageQuery> for PageRequest<C> {
+    type Error = AppError;
+
+    fn try_from(query: PageQuery) -> Result<Self, Self::Error> {
+        PageRequest::parse(query).map_err(AppError::validation)
+    }
+}
+
+impl<C: PageCursor> PageRequest<C> {
+    /// Like `try_from`, but hands back the raw errors so callers validating
+    /// more query parameters can report everything at once.
+    pub fn parse(query: PageQuery) -> Result<Self, ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+            errors.add(
+                "limit",
+                invalid(
+                    "range",
+                    format!("Limit must be between 1 and {MAX_PAGE_LIMIT}"),
+                ),
+            );
+        }
+
+        let mut decode = |field: &'static str, value: Option<String>| {
+            let value = value?;
+            let cursor = C::decode(&value);
+            if cursor.is_none() {
+                errors.add(field, invalid("cursor", "Malformed cursor".to_string()));
+            }
+            cursor
+        };
+
+        let direction = match (decode("after", query.after), decode("before", query.before)) {
+            (Some(_), Some(_)) => {
+                errors.add(
+                    "before",
+                    invalid(
+                        "exclusive",
+                        "Only one of `after` and `before` may be given".to_string(),
+                    ),
+                );
+                PageDirection::First
+            }
+            (Some(cursor), None) => PageDirection::After(cursor),
+            (None, Some(cursor)) => PageDirection::Before(cursor),
+            (None, None) => PageDirection::First,
+        };
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(PageRequest { limit, direction })
+    }
+
+    /// Restricts `query` to the requested window of the ordering given by
+    /// `keys`, whose last column must be unique. `values` returns the cursor's
+    /// value for each key, in the same order. One extra row is fetched so
+    /// `into_page` can tell whether another page follows.
+    pub fn apply_keyset<Q, K>(
+        &self,
+        query: Q,
+        keys: &[(K, Order)],
+        values: impl Fn(&C) -> Vec<Value>,
+    ) -> Q
+    where
+        Q: QueryFilter + QueryOrder + QuerySelect,
+        K: ColumnTrait,
+    {
+        // Walking backwards is the same query with every key flipped.
+        let backwards = matches!(self.direction, PageDirection::Before(_));
+        let keys: Vec<(K, Order)> = keys
+            .iter()
+            .map(|(column, order)| {
+                let order = match (order, backwards) {
+                    (Order::Asc, true) => Order::Desc,
+                    (Order::Desc, true) => Order::Asc,
+                    (order, _) => order.clone(),
+                };
+                (*column, order)
+            })
+            .collect();
+
+        let query = match &self.direction {
+            PageDirection::First => query,
+            PageDirection::After(cursor) | PageDirection::Before(cursor) => {
+                query.filter(keyset_condition(&keys, values(cursor)))
+            }
+        };
+
+        keys.into_iter()
+            .fold(query, |query, (column, order)| {
+                query.order_by(column, order)
+            })
+            .limit(self.limit + 1)
+    }
+
+    pub fn into_page<T>(self, mut rows: Vec<T>, cursor_of: impl Fn(&T) -> C) -> Page<T> {
+        let has_more = rows.len() as u64 > self.limit;
+        rows.truncate(self.limit as usize);
+
+        if let PageDirection::Before(_) = self.direction {
+            rows.reverse();
+        }
+
+        let first = rows.first().map(|row| cursor_of(row).encode());
+        let last = rows.last().map(|row| cursor_of(row).encode());
+
+        let (next_cursor, prev_cursor) = match self.direction {
+            PageDirection::First => (last.filter(|_| has_more), None),
+            PageDirection::After(_) => (last.filter(|_| has_more), first),
+            PageDirection::Before(_) => (last, first.filter(|_| has_more)),
+        };
+
+        Page {
+            data: rows,
+            next_cursor,
+            prev_cursor,
+        }
+    }
+}
+
+impl PageRequest<Cursor> {
+    pub fn apply<Q, K>(&self, query: Q, created_at: K, id: K) -> Q
+    where
+        Q: QueryFilter + QueryOrder + QuerySelect,
+        K: ColumnTrait,
+    {
+        self.apply_keyset(
+            query,
+            &[(created_at, Order::Desc), (id, Order::Desc)],
+            |cursor| vec![cursor.created_at.into(), cursor.id.into()],
+        )
+    }
+}
+
+/// Rows strictly after `values` in the lexicographic ordering `keys`.
+fn keyset_condition<K: ColumnTrait>(keys: &[(K, Order)], values: Vec<Value>) -> Condition {
+    let mut condition = Condition::any();
+
+    for (i, (column, order)) in keys.iter().enumerate() {
+        let mut branch = Condition::all();
+
+        for ((previous, _), value) in keys[..i].iter().zip(&values) {
+            branch = branch.add(previous.eq(value.clone()));
+        }
+
+        let value = values[i].clone();
+        branch = branch.add(match order {
+            Order::Asc => column.gt(value),
+            _ => column.lt(value),
+        });
+
+        condition = condition.add(branch);
+    }
+
+    condition
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            data: self.data.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            prev_cursor: self.prev_cursor,
+        }
+    }
+}
+
+pub fn invalid(code: &'static str, message: String) -> ValidationError {
+    ValidationError::new(code).with_message(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(id: i32) -> Cursor {
+        Cursor {
+            created_at: DateTime::from_timestamp_micros(1_700_000_000_123_456 + id as i64)
+                .unwrap()
+                .naive_utc(),
+            id,
+        }
+    }
+
+    fn request(limit: u64, direction: PageDirection) -> PageRequest<Cursor> {
+        PageRequest { limit, direction }
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let c = cursor(42);
+
+        assert_eq!(Cursor::decode(&c.encode()), Some(c));
+        assert_eq!(Cursor::decode("not-a-cursor"), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("1:x")), None);
+    }
+
+    #[test]
+    fn test_page_request_validates_query() {
+        let ok = PageRequest::<Cursor>::try_from(PageQuery {
+            after: Some(cursor(1).encode()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            ok,
+            request(DEFAULT_PAGE_LIMIT, PageDirection::After(cursor(1)))
+        );
+
+        for query in [
+            PageQuery {
+                limit: Some(0),
+                ..Default::default()
+            },
+            PageQuery {
+                limit: Some(MAX_PAGE_LIMIT + 1),
+                ..Default::default()
+            },
+            PageQuery {
+                after: Some("garbage".to_string()),
+                ..Default::default()
+            },
+            PageQuery {
+                after: Some(cursor(1).encode()),
+                before: Some(cursor(2).encode()),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                PageRequest::<Cursor>::try_from(query),
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_keyset_condition_is_lexicographic() {
+        use sea_orm::{DbBackend, EntityTrait, QueryTrait};
+
+        use crate::modules::product::product_entity;
+
+        let sql = request(10, PageDirection::After(cursor(7)))
+            .apply(
+                product_entity::Entity::find(),
+                product_entity::Column::CreatedAt,
+                product_entity::Column::Id,
+            )
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(sql.contains(
+            r#"WHERE "products"."created_at" < '2023-11-14 22:13:20.123463' OR ("products"."created_at" = '2023-11-14 22:13:20.123463' AND "products"."id" < 7)"#
+        ), "{sql}");
+        assert!(
+            sql.ends_with(
+                r#"ORDER BY "products"."created_at" DESC, "products"."id" DESC LIMIT 11"#
+            ),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn test_into_page_first_page() {
+        let page = request(2, PageDirection::First).into_page(vec![5, 4, 3], |id| cursor(*id));
+
+        assert_eq!(page.data, vec![5, 4]);
+        assert_eq!(page.next_cursor, Some(cursor(4).encode()));
+        assert_eq!(page.prev_cursor, None);
+
+        let last = request(5, PageDirection::First).into_page(vec![5, 4, 3], |id| cursor(*id));
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[test]
+    fn test_into_page_after_cursor() {
+        let page =
+            request(2, PageDirection::After(cursor(5))).into_page(vec![4, 3], |id| cursor(*id));
+
+        assert_eq!(page.data, vec![4, 3]);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(page.prev_cursor, Some(cursor(4).encode()));
+    }
+
+    #[test]
+    fn test_into_page_before_cursor_restores_order() {
+        let page =
+            request(2, PageDirection::Before(cursor(3))).into_page(vec![4, 5, 6], |id| cursor(*id));
+
+        assert_eq!(page.data, vec![5, 4]);
+        assert_eq!(page.next_cursor, Some(cursor(4).encode()));
+        assert_eq!(page.prev_cursor, Some(cursor(5).encode()));
+    }
+}
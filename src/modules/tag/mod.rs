@@ -0,0 +1,6 @@
+pub mod product_tag_entity;
+pub mod tag_controller;
+pub mod tag_dto;
+pub mod tag_entity;
+pub mod tag_route;
+pub mod tag_service;
@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::tag_entity;
+use crate::modules::product::product_entity;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "product_tags")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub product_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub tag_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "product_entity::Entity",
+        from = "Column::ProductId",
+        to = "product_entity::Column::Id",
+        on_delete = "Cascade",
+        on_update = "Cascade"
+    )]
+    Product,
+    #[sea_orm(
+        belongs_to = "tag_entity::Entity",
+        from = "Column::TagId",
+        to = "tag_entity::Column::Id",
+        on_delete = "Cascade",
+        on_update = "Cascade"
+    )]
+    Tag,
+}
+
+impl Related<product_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Product.def()
+    }
+}
+
+impl Related<tag_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tag.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
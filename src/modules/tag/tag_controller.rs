@@ -0,0 +1,22 @@
+use axum::{Json, extract::State, http::StatusCode};
+
+use super::{tag_dto::TagUsageResponse, tag_service::TagService};
+use crate::{
+    middleware::{ProductsRead, RequireScope},
+    modules::shared::error::AppError,
+    state::AppState,
+};
+
+pub async fn find_tags_handler(
+    State(state): State<AppState>,
+    _scope: RequireScope<ProductsRead>,
+) -> Result<(StatusCode, Json<Vec<TagUsageResponse>>), AppError> {
+    let tags = TagService::find_tags_with_usage(&state.db)
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(tags.into_iter().map(Into::into).collect()),
+    ))
+}
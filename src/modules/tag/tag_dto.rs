@@ -0,0 +1,20 @@
+use serde::Serialize;
+
+use super::tag_service::TagUsage;
+
+#[derive(Debug, Serialize)]
+pub struct TagUsageResponse {
+    pub id: i32,
+    pub name: String,
+    pub product_count: i64,
+}
+
+impl From<TagUsage> for TagUsageResponse {
+    fn from(tag: TagUsage) -> Self {
+        TagUsageResponse {
+            id: tag.id,
+            name: tag.name,
+            product_count: tag.product_count,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::product_tag_entity;
+use crate::modules::product::product_entity;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "tags")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub name: String,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "product_tag_entity::Entity")]
+    ProductTags,
+}
+
+impl Related<product_tag_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductTags.def()
+    }
+}
+
+impl Related<product_entity::Entity> for Entity {
+    fn to() -> RelationDef {
+        product_tag_entity::Relation::Product.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(product_tag_entity::Relation::Tag.def().rev())
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,8 @@
+use axum::{Router, routing::get};
+
+use super::tag_controller::find_tags_handler;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(find_tags_handler))
+}
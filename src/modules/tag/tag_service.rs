@@ -0,0 +1,164 @@
+use anyhow::Result;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
+    ModelTrait, QueryFilter, QueryOrder, Set, Statement, sea_query::OnConflict,
+};
+
+use super::{product_tag_entity, tag_entity};
+use crate::modules::product::product_entity;
+
+pub const MAX_TAG_LENGTH: usize = 50;
+
+const TAG_USAGE_SQL: &str = r#"
+SELECT t.id, t.name, COUNT(p.id) AS product_count
+FROM tags t
+LEFT JOIN product_tags pt ON pt.tag_id = t.id
+LEFT JOIN products p ON p.id = pt.product_id AND p.deleted_at IS NULL
+GROUP BY t.id, t.name
+ORDER BY product_count DESC, t.name ASC
+"#;
+
+#[derive(Debug, FromQueryResult)]
+pub struct TagUsage {
+    pub id: i32,
+    pub name: String,
+    pub product_count: i64,
+}
+
+#[derive(Clone)]
+pub struct TagService;
+
+impl TagService {
+    /// Every tag with the number of live products carrying it, most used first.
+    pub async fn find_tags_with_usage(db: &DatabaseConnection) -> Result<Vec<TagUsage>> {
+        let tags =
+            TagUsage::find_by_statement(Statement::from_string(DbBackend::Postgres, TAG_USAGE_SQL))
+                .all(db)
+                .await?;
+        Ok(tags)
+    }
+
+    pub async fn find_product_tags<C: ConnectionTrait>(
+        db: &C,
+        product: &product_entity::Model,
+    ) -> Result<Vec<tag_entity::Model>> {
+        let tags = product
+            .find_related(tag_entity::Entity)
+            .order_by_asc(tag_entity::Column::Name)
+            .all(db)
+            .await?;
+        Ok(tags)
+    }
+
+    /// Replaces the product's tags with `names`, which must already be
+    /// normalized. Unknown names become new tags.
+    pub async fn set_product_tags<C: ConnectionTrait>(
+        db: &C,
+        product_id: i32,
+        names: &[String],
+    ) -> Result<()> {
+        product_tag_entity::Entity::delete_many()
+            .filter(product_tag_entity::Column::ProductId.eq(product_id))
+            .exec(db)
+            .await?;
+
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        let tags = Self::find_or_create_tags(db, names).await?;
+        let links = tags.into_iter().map(|tag| product_tag_entity::ActiveModel {
+            product_id: Set(product_id),
+            tag_id: Set(tag.id),
+        });
+
+        product_tag_entity::Entity::insert_many(links)
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    async fn find_or_create_tags<C: ConnectionTrait>(
+        db: &C,
+        names: &[String],
+    ) -> Result<Vec<tag_entity::Model>> {
+        let new_tags = names.iter().map(|name| tag_entity::ActiveModel {
+            name: Set(name.clone()),
+            ..Default::default()
+        });
+
+        // Concurrent requests may create the same tag; whoever loses the
+        // race just picks up the existing row below.
+        tag_entity::Entity::insert_many(new_tags)
+            .on_conflict(
+                OnConflict::column(tag_entity::Column::Name)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .do_nothing()
+            .exec(db)
+            .await?;
+
+        let tags = tag_entity::Entity::find()
+            .filter(tag_entity::Column::Name.is_in(names.iter().cloned()))
+            .all(db)
+            .await?;
+        Ok(tags)
+    }
+}
+
+/// Lowercases a tag and collapses runs of whitespace, so "Summer  Sale" and
+/// "summer sale" are the same tag.
+pub fn normalize_tag_name(name: &str) -> Option<String> {
+    let normalized = name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    let length = normalized.chars().count();
+    (1..=MAX_TAG_LENGTH).contains(&length).then_some(normalized)
+}
+
+/// Normalizes and de-duplicates tag names, keeping their first-seen order.
+pub fn normalize_tag_names(names: &[String]) -> Option<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::with_capacity(names.len());
+
+    for name in names {
+        let name = normalize_tag_name(name)?;
+        if !normalized.contains(&name) {
+            normalized.push(name);
+        }
+    }
+
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag_name() {
+        assert_eq!(
+            normalize_tag_name("  Summer \t Sale "),
+            Some("summer sale".to_string())
+        );
+        assert_eq!(normalize_tag_name("   "), None);
+        assert_eq!(normalize_tag_name(&"x".repeat(MAX_TAG_LENGTH + 1)), None);
+    }
+
+    #[test]
+    fn test_normalize_tag_names_deduplicates() {
+        let names = ["Red".to_string(), "blue".to_string(), "RED ".to_string()];
+
+        assert_eq!(
+            normalize_tag_names(&names),
+            Some(vec!["red".to_string(), "blue".to_string()])
+        );
+        assert_eq!(
+            normalize_tag_names(&["ok".to_string(), "".to_string()]),
+            None
+        );
+    }
+}
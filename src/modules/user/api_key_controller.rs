@@ -0,0 +1,70 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{Duration, Utc};
+use validator::Validate;
+
+use super::api_key_service::ApiKeyService;
+use super::user_dto::{ApiKeyResponse, CreateApiKeyPayload, CreateApiKeyResponse};
+use crate::{middleware::SessionClaims, modules::shared::error::AppError, state::AppState};
+
+pub async fn create_api_key_handler(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Json(payload): Json<CreateApiKeyPayload>,
+) -> Result<(StatusCode, Json<CreateApiKeyResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let expires_at = payload
+        .expires_in_days
+        .map(|days| (Utc::now() + Duration::days(days)).naive_utc());
+
+    let (api_key, key) = ApiKeyService::create_api_key(
+        &state.db,
+        claims.sub,
+        payload.name,
+        payload.scopes,
+        expires_at,
+    )
+    .await
+    .map_err(AppError::internal)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateApiKeyResponse {
+            api_key: api_key.into(),
+            key,
+        }),
+    ))
+}
+
+pub async fn find_api_keys_handler(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+) -> Result<(StatusCode, Json<Vec<ApiKeyResponse>>), AppError> {
+    let api_keys = ApiKeyService::find_api_keys_by_user(&state.db, claims.sub)
+        .await
+        .map_err(AppError::internal)?;
+
+    let response: Vec<ApiKeyResponse> = api_keys.into_iter().map(Into::into).collect();
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+pub async fn revoke_api_key_handler(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Path(key_id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    let revoked = ApiKeyService::revoke_api_key(&state.db, claims.sub, key_id)
+        .await
+        .map_err(AppError::internal)?;
+
+    if !revoked {
+        return Err(AppError::NotFound("API key not found".to_string()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,171 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime, Utc};
+use password_hash::rand_core::{OsRng, RngCore};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set, prelude::Expr,
+};
+
+use super::{
+    api_key_entity::{self, ApiScope},
+    user_entity,
+};
+use crate::utils::{
+    api_key_cache::ApiKeyCache,
+    hash::{hash_password, verify_password_blocking},
+    token::generate_opaque_token,
+};
+
+pub const API_KEY_PREFIX: &str = "ask_";
+
+const LAST_USED_RESOLUTION_SECONDS: i64 = 60;
+
+#[derive(Clone)]
+pub struct ApiKeyService;
+
+impl ApiKeyService {
+    /// Creates a key and returns it together with the plaintext secret, which
+    /// is never stored and cannot be shown again.
+    pub async fn create_api_key(
+        db: &DatabaseConnection,
+        user_id: i32,
+        name: String,
+        scopes: Vec<ApiScope>,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<(api_key_entity::Model, String)> {
+        let mut prefix_bytes = [0u8; 6];
+        OsRng.fill_bytes(&mut prefix_bytes);
+        let prefix = hex::encode(prefix_bytes);
+        let secret = generate_opaque_token();
+
+        let api_key = api_key_entity::ActiveModel {
+            user_id: Set(user_id),
+            name: Set(name),
+            prefix: Set(prefix.clone()),
+            secret_hash: Set(hash_password(&secret)),
+            scopes: Set(scopes
+                .iter()
+                .map(|scope| scope.as_str().to_string())
+                .collect()),
+            expires_at: Set(expires_at),
+            ..Default::default()
+        };
+
+        let inserted = api_key.insert(db).await?;
+        Ok((inserted, format!("{API_KEY_PREFIX}{prefix}_{secret}")))
+    }
+
+    pub async fn find_api_keys_by_user(
+        db: &DatabaseConnection,
+        user_id: i32,
+    ) -> Result<Vec<api_key_entity::Model>> {
+        let api_keys = api_key_entity::Entity::find()
+            .filter(api_key_entity::Column::UserId.eq(user_id))
+            .filter(api_key_entity::Column::RevokedAt.is_null())
+            .order_by_asc(api_key_entity::Column::Id)
+            .all(db)
+            .await?;
+        Ok(api_keys)
+    }
+
+    pub async fn revoke_api_key(
+        db: &DatabaseConnection,
+        user_id: i32,
+        key_id: i32,
+    ) -> Result<bool> {
+        let result = api_key_entity::Entity::update_many()
+            .col_expr(
+                api_key_entity::Column::RevokedAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(api_key_entity::Column::Id.eq(key_id))
+            .filter(api_key_entity::Column::UserId.eq(user_id))
+            .filter(api_key_entity::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Resolves a presented `ask_...` key to its record and owner, or `None`
+    /// if it is malformed, unknown, revoked, expired or has a wrong secret.
+    /// The secret is checked on the blocking pool unless `cache` saw it verify
+    /// recently.
+    pub async fn authenticate(
+        db: &DatabaseConnection,
+        cache: &ApiKeyCache,
+        key: &str,
+    ) -> Result<Option<(api_key_entity::Model, user_entity::Model)>> {
+        let Some((prefix, secret)) = split_api_key(key) else {
+            return Ok(None);
+        };
+
+        let Some((api_key, Some(user))) = api_key_entity::Entity::find()
+            .filter(api_key_entity::Column::Prefix.eq(prefix))
+            .find_also_related(user_entity::Entity)
+            .one(db)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let now = Utc::now().naive_utc();
+
+        if api_key.revoked_at.is_some()
+            || api_key
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= now)
+        {
+            return Ok(None);
+        }
+
+        if !cache.is_verified(prefix, secret, &api_key.secret_hash) {
+            if !verify_password_blocking(secret.to_string(), api_key.secret_hash.clone()).await? {
+                return Ok(None);
+            }
+            cache.insert(prefix, secret, &api_key.secret_hash);
+        }
+
+        // Only write when the stored value is stale, to avoid an UPDATE per request.
+        api_key_entity::Entity::update_many()
+            .col_expr(api_key_entity::Column::LastUsedAt, Expr::value(now))
+            .filter(api_key_entity::Column::Id.eq(api_key.id))
+            .filter(
+                Condition::any()
+                    .add(api_key_entity::Column::LastUsedAt.is_null())
+                    .add(
+                        api_key_entity::Column::LastUsedAt
+                            .lt(now - Duration::seconds(LAST_USED_RESOLUTION_SECONDS)),
+                    ),
+            )
+            .exec(db)
+            .await?;
+
+        Ok(Some((api_key, user)))
+    }
+}
+
+pub fn split_api_key(key: &str) -> Option<(&str, &str)> {
+    let (prefix, secret) = key.strip_prefix(API_KEY_PREFIX)?.split_once('_')?;
+
+    if prefix.is_empty() || secret.is_empty() {
+        return None;
+    }
+
+    Some((prefix, secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_api_key() {
+        assert_eq!(
+            split_api_key("ask_0a1b2c3d4e5f_se_cr-et"),
+            Some(("0a1b2c3d4e5f", "se_cr-et"))
+        );
+        assert_eq!(split_api_key("ask_0a1b2c3d4e5f"), None);
+        assert_eq!(split_api_key("ask__secret"), None);
+        assert_eq!(split_api_key("eyJhbGciOi.jwt.token"), None);
+    }
+}
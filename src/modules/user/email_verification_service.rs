@@ -0,0 +1,105 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+    TransactionTrait, prelude::Expr,
+};
+
+use super::{email_verification_token_entity, user_entity};
+use crate::{
+    config::account::AccountConfig,
+    mailer::{Email, Mailer},
+    utils::token::{generate_opaque_token, hash_token},
+};
+
+#[derive(Clone)]
+pub struct EmailVerificationService;
+
+impl EmailVerificationService {
+    pub async fn issue_verification_token(
+        db: &DatabaseConnection,
+        user_id: i32,
+        ttl_hours: i64,
+    ) -> Result<String> {
+        let token = generate_opaque_token();
+
+        let verification_token = email_verification_token_entity::ActiveModel {
+            user_id: Set(user_id),
+            token_hash: Set(hash_token(&token)),
+            expires_at: Set((Utc::now() + Duration::hours(ttl_hours)).naive_utc()),
+            ..Default::default()
+        };
+
+        verification_token.insert(db).await?;
+        Ok(token)
+    }
+
+    /// Consumes a verification token and marks the owning user as verified.
+    pub async fn verify_email(
+        db: &DatabaseConnection,
+        token: &str,
+    ) -> Result<Option<user_entity::Model>> {
+        let txn = db.begin().await?;
+        let now = Utc::now().naive_utc();
+
+        let Some(verification_token) = email_verification_token_entity::Entity::find()
+            .filter(email_verification_token_entity::Column::TokenHash.eq(hash_token(token)))
+            .filter(email_verification_token_entity::Column::UsedAt.is_null())
+            .filter(email_verification_token_entity::Column::ExpiresAt.gt(now))
+            .one(&txn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        // Retire every outstanding token for the user, not just the one presented.
+        email_verification_token_entity::Entity::update_many()
+            .col_expr(
+                email_verification_token_entity::Column::UsedAt,
+                Expr::value(now),
+            )
+            .filter(email_verification_token_entity::Column::UserId.eq(verification_token.user_id))
+            .filter(email_verification_token_entity::Column::UsedAt.is_null())
+            .exec(&txn)
+            .await?;
+
+        let Some(user) = user_entity::Entity::find_by_id(verification_token.user_id)
+            .one(&txn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let user = if user.email_verified_at.is_none() {
+            let mut active_model: user_entity::ActiveModel = user.into();
+            active_model.email_verified_at = Set(Some(now));
+            active_model.update(&txn).await?
+        } else {
+            user
+        };
+
+        txn.commit().await?;
+        Ok(Some(user))
+    }
+
+    pub async fn send_verification_email(
+        mailer: &dyn Mailer,
+        account_config: &AccountConfig,
+        email: &str,
+        token: &str,
+    ) -> Result<()> {
+        let link = format!("{}/verify-email?token={token}", account_config.app_url);
+
+        mailer
+            .send(Email {
+                to: email.to_string(),
+                subject: "Verify your email address".to_string(),
+                body: format!(
+                    "Confirm your email address by opening the link below:\n\n{link}\n\n\
+                     The link expires in {} hours.",
+                    account_config.email_verification_ttl_hours
+                ),
+            })
+            .await
+    }
+}
@@ -0,0 +1,117 @@
+use axum::{Json, extract::State, http::StatusCode};
+use validator::Validate;
+
+use super::mfa_service::MfaService;
+use super::user_dto::{MfaCodePayload, RecoveryCodesResponse, TotpEnrollmentResponse};
+use super::user_service::UserService;
+use crate::{
+    middleware::SessionClaims, modules::shared::error::AppError, state::AppState, utils::totp,
+};
+
+pub async fn enroll_totp_handler(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+) -> Result<(StatusCode, Json<TotpEnrollmentResponse>), AppError> {
+    let user = UserService::find_user_by_id(&state.db, claims.sub)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let credential = MfaService::start_totp_enrollment(&state.db, user.id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| {
+            AppError::Conflict("Two-factor authentication is already enabled".to_string())
+        })?;
+
+    let otpauth_uri = totp::otpauth_uri(
+        &state.account_config.totp_issuer,
+        &user.email,
+        &credential.secret,
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(TotpEnrollmentResponse {
+            secret: credential.secret,
+            otpauth_uri,
+        }),
+    ))
+}
+
+pub async fn confirm_totp_handler(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Json(payload): Json<MfaCodePayload>,
+) -> Result<(StatusCode, Json<RecoveryCodesResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let recovery_codes = MfaService::confirm_totp_enrollment(&state.db, claims.sub, &payload.code)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| {
+            AppError::Unauthorized("Invalid code or no pending enrollment".to_string())
+        })?;
+
+    Ok((
+        StatusCode::OK,
+        Json(RecoveryCodesResponse { recovery_codes }),
+    ))
+}
+
+pub async fn disable_totp_handler(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Json(payload): Json<MfaCodePayload>,
+) -> Result<StatusCode, AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    require_second_factor(&state, claims.sub, &payload.code).await?;
+
+    MfaService::disable_totp(&state.db, claims.sub)
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn regenerate_recovery_codes_handler(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Json(payload): Json<MfaCodePayload>,
+) -> Result<(StatusCode, Json<RecoveryCodesResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    require_second_factor(&state, claims.sub, &payload.code).await?;
+
+    let recovery_codes = MfaService::regenerate_recovery_codes(&state.db, claims.sub)
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(RecoveryCodesResponse { recovery_codes }),
+    ))
+}
+
+async fn require_second_factor(state: &AppState, user_id: i32, code: &str) -> Result<(), AppError> {
+    if !MfaService::is_totp_enabled(&state.db, user_id)
+        .await
+        .map_err(AppError::internal)?
+    {
+        return Err(AppError::NotFound(
+            "Two-factor authentication is not enabled".to_string(),
+        ));
+    }
+
+    if !MfaService::verify_second_factor(&state.db, user_id, code)
+        .await
+        .map_err(AppError::internal)?
+    {
+        return Err(AppError::Unauthorized(
+            "Invalid authentication code".to_string(),
+        ));
+    }
+
+    Ok(())
+}
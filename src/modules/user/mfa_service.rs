@@ -0,0 +1,301 @@
+use anyhow::Result;
+use chrono::Utc;
+use data_encoding::BASE32_NOPAD;
+use password_hash::rand_core::{OsRng, RngCore};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, Set, TransactionTrait, prelude::Expr,
+};
+
+use super::{mfa_recovery_code_entity, totp_credential_entity};
+use crate::utils::{
+    hash::{hash_password, verify_password},
+    totp,
+};
+
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_BYTES: usize = 5;
+
+#[derive(Clone)]
+pub struct MfaService;
+
+impl MfaService {
+    pub async fn find_totp_credential(
+        db: &DatabaseConnection,
+        user_id: i32,
+    ) -> Result<Option<totp_credential_entity::Model>> {
+        let credential = totp_credential_entity::Entity::find()
+            .filter(totp_credential_entity::Column::UserId.eq(user_id))
+            .one(db)
+            .await?;
+        Ok(credential)
+    }
+
+    pub async fn is_totp_enabled(db: &DatabaseConnection, user_id: i32) -> Result<bool> {
+        let credential = Self::find_totp_credential(db, user_id).await?;
+        Ok(credential.is_some_and(|credential| credential.confirmed_at.is_some()))
+    }
+
+    /// Stores a fresh unconfirmed secret, replacing any earlier unfinished
+    /// enrollment. Returns `None` if TOTP is already enabled.
+    pub async fn start_totp_enrollment(
+        db: &DatabaseConnection,
+        user_id: i32,
+    ) -> Result<Option<totp_credential_entity::Model>> {
+        let txn = db.begin().await?;
+
+        let existing = totp_credential_entity::Entity::find()
+            .filter(totp_credential_entity::Column::UserId.eq(user_id))
+            .one(&txn)
+            .await?;
+
+        if let Some(existing) = existing {
+            if existing.confirmed_at.is_some() {
+                return Ok(None);
+            }
+
+            totp_credential_entity::Entity::delete_by_id(existing.id)
+                .exec(&txn)
+                .await?;
+        }
+
+        let credential = totp_credential_entity::ActiveModel {
+            user_id: Set(user_id),
+            secret: Set(totp::generate_secret()),
+            ..Default::default()
+        };
+
+        let inserted = credential.insert(&txn).await?;
+        txn.commit().await?;
+
+        Ok(Some(inserted))
+    }
+
+    /// Enables TOTP once the user proves their authenticator works and
+    /// returns the plaintext recovery codes, which are only shown this once.
+    pub async fn confirm_totp_enrollment(
+        db: &DatabaseConnection,
+        user_id: i32,
+        code: &str,
+    ) -> Result<Option<Vec<String>>> {
+        let txn = db.begin().await?;
+
+        let Some(credential) = totp_credential_entity::Entity::find()
+            .filter(totp_credential_entity::Column::UserId.eq(user_id))
+            .filter(totp_credential_entity::Column::ConfirmedAt.is_null())
+            .one(&txn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let Some(step) = totp::verify_code(&credential.secret, code, Utc::now().timestamp()) else {
+            return Ok(None);
+        };
+
+        let mut active_model: totp_credential_entity::ActiveModel = credential.into();
+        active_model.confirmed_at = Set(Some(Utc::now().naive_utc()));
+        active_model.last_used_step = Set(Some(step));
+        active_model.update(&txn).await?;
+
+        let codes = Self::replace_recovery_codes(&txn, user_id).await?;
+        txn.commit().await?;
+
+        Ok(Some(codes))
+    }
+
+    /// Accepts either a current TOTP code or an unused recovery code. Each
+    /// TOTP step and each recovery code can only be redeemed once.
+    pub async fn verify_second_factor(
+        db: &DatabaseConnection,
+        user_id: i32,
+        code: &str,
+    ) -> Result<bool> {
+        let Some(credential) = Self::find_totp_credential(db, user_id).await? else {
+            return Ok(false);
+        };
+
+        if credential.confirmed_at.is_none() {
+            return Ok(false);
+        }
+
+        if let Some(step) = totp::verify_code(&credential.secret, code, Utc::now().timestamp()) {
+            let result = totp_credential_entity::Entity::update_many()
+                .col_expr(
+                    totp_credential_entity::Column::LastUsedStep,
+                    Expr::value(step),
+                )
+                .filter(totp_credential_entity::Column::Id.eq(credential.id))
+                .filter(
+                    Condition::any()
+                        .add(totp_credential_entity::Column::LastUsedStep.is_null())
+                        .add(totp_credential_entity::Column::LastUsedStep.lt(step)),
+                )
+                .exec(db)
+                .await?;
+            return Ok(result.rows_affected > 0);
+        }
+
+        Self::redeem_recovery_code(db, user_id, code).await
+    }
+
+    pub async fn regenerate_recovery_codes(
+        db: &DatabaseConnection,
+        user_id: i32,
+    ) -> Result<Vec<String>> {
+        let txn = db.begin().await?;
+        let codes = Self::replace_recovery_codes(&txn, user_id).await?;
+        txn.commit().await?;
+        Ok(codes)
+    }
+
+    pub async fn disable_totp(db: &DatabaseConnection, user_id: i32) -> Result<()> {
+        let txn = db.begin().await?;
+
+        totp_credential_entity::Entity::delete_many()
+            .filter(totp_credential_entity::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        mfa_recovery_code_entity::Entity::delete_many()
+            .filter(mfa_recovery_code_entity::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn redeem_recovery_code(
+        db: &DatabaseConnection,
+        user_id: i32,
+        code: &str,
+    ) -> Result<bool> {
+        let Some(code) = normalize_recovery_code(code) else {
+            return Ok(false);
+        };
+
+        let candidates = mfa_recovery_code_entity::Entity::find()
+            .filter(mfa_recovery_code_entity::Column::UserId.eq(user_id))
+            .filter(mfa_recovery_code_entity::Column::UsedAt.is_null())
+            .all(db)
+            .await?;
+
+        // Up to RECOVERY_CODE_COUNT argon2 checks, so keep them off the runtime.
+        let matched = tokio::task::spawn_blocking(move || {
+            candidates
+                .into_iter()
+                .find(|candidate| verify_password(&code, &candidate.code_hash))
+        })
+        .await?;
+        let Some(matched) = matched else {
+            return Ok(false);
+        };
+
+        let result = mfa_recovery_code_entity::Entity::update_many()
+            .col_expr(
+                mfa_recovery_code_entity::Column::UsedAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(mfa_recovery_code_entity::Column::Id.eq(matched.id))
+            .filter(mfa_recovery_code_entity::Column::UsedAt.is_null())
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    async fn replace_recovery_codes<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+    ) -> Result<Vec<String>> {
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| generate_recovery_code())
+            .collect();
+
+        // Hashed on the blocking pool, before touching the rows.
+        let hashes = tokio::task::spawn_blocking({
+            let codes = codes.clone();
+            move || {
+                codes
+                    .iter()
+                    .map(|code| {
+                        let normalized =
+                            normalize_recovery_code(code).expect("generated codes are valid");
+                        hash_password(&normalized)
+                    })
+                    .collect::<Vec<_>>()
+            }
+        })
+        .await?;
+
+        mfa_recovery_code_entity::Entity::delete_many()
+            .filter(mfa_recovery_code_entity::Column::UserId.eq(user_id))
+            .exec(db)
+            .await?;
+
+        let models = hashes
+            .into_iter()
+            .map(|code_hash| mfa_recovery_code_entity::ActiveModel {
+                user_id: Set(user_id),
+                code_hash: Set(code_hash),
+                ..Default::default()
+            });
+
+        mfa_recovery_code_entity::Entity::insert_many(models)
+            .exec(db)
+            .await?;
+
+        Ok(codes)
+    }
+}
+
+pub fn generate_recovery_code() -> String {
+    let mut bytes = [0u8; RECOVERY_CODE_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    let encoded = BASE32_NOPAD.encode(&bytes).to_lowercase();
+    let (head, tail) = encoded.split_at(encoded.len() / 2);
+    format!("{head}-{tail}")
+}
+
+/// Strips the separator and whitespace users tend to add or drop when typing
+/// a recovery code, and lowercases it.
+pub fn normalize_recovery_code(code: &str) -> Option<String> {
+    let normalized: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    let valid = normalized.len() == RECOVERY_CODE_BYTES * 8 / 5
+        && normalized
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c));
+
+    valid.then_some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_recovery_codes_normalize() {
+        let code = generate_recovery_code();
+
+        assert_eq!(code.len(), 9);
+        assert_eq!(code.chars().nth(4), Some('-'));
+        assert_eq!(normalize_recovery_code(&code), Some(code.replace('-', "")));
+    }
+
+    #[test]
+    fn test_normalize_recovery_code() {
+        assert_eq!(
+            normalize_recovery_code(" ABCD-EFG2 "),
+            Some("abcdefg2".to_string())
+        );
+        assert_eq!(normalize_recovery_code("abcd-efg"), None);
+        assert_eq!(normalize_recovery_code("abcd-efg1"), None);
+        assert_eq!(normalize_recovery_code("123456"), None);
+    }
+}
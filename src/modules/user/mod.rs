@@ -1,3 +1,18 @@
+pub mod api_key_controller;
+pub mod api_key_entity;
+pub mod api_key_service;
+pub mod email_verification_service;
+pub mod email_verification_token_entity;
+pub mod mfa_controller;
+pub mod mfa_recovery_code_entity;
+pub mod mfa_service;
+pub mod password_reset_service;
+pub mod password_reset_token_entity;
+pub mod refresh_token_entity;
+pub mod refresh_token_service;
+pub mod revoked_token_entity;
+pub mod revoked_token_service;
+pub mod totp_credential_entity;
 pub mod user_controller;
 pub mod user_dto;
 pub mod user_entity;
@@ -0,0 +1,146 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait, prelude::Expr,
+};
+
+use super::{password_reset_token_entity, refresh_token_service::RefreshTokenService, user_entity};
+use crate::{
+    config::account::AccountConfig,
+    mailer::{Email, Mailer},
+    utils::{
+        hash::hash_password_blocking,
+        token::{generate_opaque_token, hash_token},
+    },
+};
+
+#[derive(Clone)]
+pub struct PasswordResetService;
+
+impl PasswordResetService {
+    pub async fn issue_reset_token(
+        db: &DatabaseConnection,
+        user_id: i32,
+        ttl_minutes: i64,
+    ) -> Result<String> {
+        let token = generate_opaque_token();
+
+        let reset_token = password_reset_token_entity::ActiveModel {
+            user_id: Set(user_id),
+            token_hash: Set(hash_token(&token)),
+            expires_at: Set((Utc::now() + Duration::minutes(ttl_minutes)).naive_utc()),
+            ..Default::default()
+        };
+
+        reset_token.insert(db).await?;
+        Ok(token)
+    }
+
+    /// Consumes a reset token, stores the new password and revokes every
+    /// session of the user. Returns the updated user, or `None` when the token
+    /// is unknown, used or expired. The token row is locked, so concurrent
+    /// resets with the same token cannot both redeem it.
+    pub async fn reset_password(
+        db: &DatabaseConnection,
+        token: &str,
+        new_password: &str,
+    ) -> Result<Option<user_entity::Model>> {
+        let password_hash = hash_password_blocking(new_password.to_string()).await?;
+
+        let txn = db.begin().await?;
+        let now = Utc::now().naive_utc();
+
+        let Some(reset_token) = password_reset_token_entity::Entity::find()
+            .filter(password_reset_token_entity::Column::TokenHash.eq(hash_token(token)))
+            .filter(password_reset_token_entity::Column::UsedAt.is_null())
+            .filter(password_reset_token_entity::Column::ExpiresAt.gt(now))
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        password_reset_token_entity::Entity::update_many()
+            .col_expr(
+                password_reset_token_entity::Column::UsedAt,
+                Expr::value(now),
+            )
+            .filter(password_reset_token_entity::Column::UserId.eq(reset_token.user_id))
+            .filter(password_reset_token_entity::Column::UsedAt.is_null())
+            .exec(&txn)
+            .await?;
+
+        let Some(user) = user_entity::Entity::find_by_id(reset_token.user_id)
+            .one(&txn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let mut active_model: user_entity::ActiveModel = user.into();
+        active_model.password = Set(password_hash);
+        active_model.sessions_revoked_at = Set(Some(now));
+        let user = active_model.update(&txn).await?;
+
+        RefreshTokenService::revoke_all_for_user(&txn, user.id).await?;
+
+        txn.commit().await?;
+        Ok(Some(user))
+    }
+
+    pub async fn send_reset_email(
+        mailer: &dyn Mailer,
+        account_config: &AccountConfig,
+        email: &str,
+        token: &str,
+    ) -> Result<()> {
+        let link = format!("{}/reset-password?token={token}", account_config.app_url);
+
+        mailer
+            .send(Email {
+                to: email.to_string(),
+                subject: "Reset your password".to_string(),
+                body: format!(
+                    "Someone asked to reset the password for this account. If it was you, \
+                     open the link below:\n\n{link}\n\nThe link expires in {} minutes. \
+                     If you did not ask for this, you can ignore this email.",
+                    account_config.password_reset_ttl_minutes
+                ),
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mailer::memory::MemoryMailer;
+
+    #[tokio::test]
+    async fn test_send_reset_email_includes_link_with_token() {
+        let mailer = MemoryMailer::default();
+        let account_config = AccountConfig {
+            app_url: "https://shop.example".to_string(),
+            email_verification_ttl_hours: 24,
+            password_reset_ttl_minutes: 30,
+            require_verified_email: false,
+            totp_issuer: "axum-sea".to_string(),
+            mfa_token_ttl_minutes: 5,
+        };
+
+        PasswordResetService::send_reset_email(&mailer, &account_config, "a@b.c", "tok")
+            .await
+            .unwrap();
+
+        let sent = mailer.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "a@b.c");
+        assert!(
+            sent[0]
+                .body
+                .contains("https://shop.example/reset-password?token=tok")
+        );
+    }
+}
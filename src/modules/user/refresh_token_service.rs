@@ -0,0 +1,145 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    Set, TransactionTrait, prelude::Expr,
+};
+use uuid::Uuid;
+
+use super::refresh_token_entity;
+use crate::utils::token::{generate_opaque_token, hash_token};
+
+pub struct IssuedRefreshToken {
+    pub token: String,
+    pub expires_at: i64,
+}
+
+#[derive(Clone)]
+pub struct RefreshTokenService;
+
+impl RefreshTokenService {
+    pub async fn issue_refresh_token(
+        db: &DatabaseConnection,
+        user_id: i32,
+        ttl_days: i64,
+    ) -> Result<IssuedRefreshToken> {
+        Self::insert_refresh_token(db, user_id, Uuid::new_v4(), ttl_days).await
+    }
+
+    /// Exchanges a refresh token for a new one in the same family.
+    ///
+    /// Returns `None` when the token is unknown, expired or revoked. Presenting a
+    /// token that has already been rotated revokes its whole family.
+    pub async fn rotate_refresh_token(
+        db: &DatabaseConnection,
+        token: &str,
+        ttl_days: i64,
+    ) -> Result<Option<(i32, IssuedRefreshToken)>> {
+        let txn = db.begin().await?;
+
+        let Some(existing) = refresh_token_entity::Entity::find()
+            .filter(refresh_token_entity::Column::TokenHash.eq(hash_token(token)))
+            .one(&txn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let now = Utc::now().naive_utc();
+
+        if existing.revoked_at.is_some() || existing.expires_at <= now {
+            return Ok(None);
+        }
+
+        // Claim the token with a conditional update so two concurrent refreshes
+        // cannot both rotate it; the loser is treated as a reuse.
+        let claimed = refresh_token_entity::Entity::update_many()
+            .col_expr(refresh_token_entity::Column::UsedAt, Expr::value(now))
+            .filter(refresh_token_entity::Column::Id.eq(existing.id))
+            .filter(refresh_token_entity::Column::UsedAt.is_null())
+            .exec(&txn)
+            .await?;
+
+        if claimed.rows_affected == 0 {
+            Self::revoke_family(&txn, existing.family_id).await?;
+            txn.commit().await?;
+            return Ok(None);
+        }
+
+        let issued =
+            Self::insert_refresh_token(&txn, existing.user_id, existing.family_id, ttl_days)
+                .await?;
+
+        txn.commit().await?;
+        Ok(Some((existing.user_id, issued)))
+    }
+
+    pub async fn revoke_refresh_token(
+        db: &DatabaseConnection,
+        user_id: i32,
+        token: &str,
+    ) -> Result<bool> {
+        let Some(existing) = refresh_token_entity::Entity::find()
+            .filter(refresh_token_entity::Column::TokenHash.eq(hash_token(token)))
+            .filter(refresh_token_entity::Column::UserId.eq(user_id))
+            .one(db)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        Self::revoke_family(db, existing.family_id).await?;
+        Ok(true)
+    }
+
+    pub async fn revoke_family<C: ConnectionTrait>(db: &C, family_id: Uuid) -> Result<u64> {
+        let result = refresh_token_entity::Entity::update_many()
+            .col_expr(
+                refresh_token_entity::Column::RevokedAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(refresh_token_entity::Column::FamilyId.eq(family_id))
+            .filter(refresh_token_entity::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    pub async fn revoke_all_for_user<C: ConnectionTrait>(db: &C, user_id: i32) -> Result<u64> {
+        let result = refresh_token_entity::Entity::update_many()
+            .col_expr(
+                refresh_token_entity::Column::RevokedAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(refresh_token_entity::Column::UserId.eq(user_id))
+            .filter(refresh_token_entity::Column::RevokedAt.is_null())
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    async fn insert_refresh_token<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        family_id: Uuid,
+        ttl_days: i64,
+    ) -> Result<IssuedRefreshToken> {
+        let token = generate_opaque_token();
+        let expires_at = Utc::now() + Duration::days(ttl_days);
+
+        let refresh_token = refresh_token_entity::ActiveModel {
+            user_id: Set(user_id),
+            family_id: Set(family_id),
+            token_hash: Set(hash_token(&token)),
+            expires_at: Set(expires_at.naive_utc()),
+            ..Default::default()
+        };
+
+        refresh_token.insert(db).await?;
+
+        Ok(IssuedRefreshToken {
+            token,
+            expires_at: expires_at.timestamp(),
+        })
+    }
+}
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, sea_query::OnConflict,
+};
+
+use super::{revoked_token_entity, user_entity};
+use crate::utils::revocation::RevocationCache;
+
+#[derive(Clone)]
+pub struct RevokedTokenService;
+
+impl RevokedTokenService {
+    pub async fn revoke_token(
+        db: &DatabaseConnection,
+        cache: &RevocationCache,
+        jti: &str,
+        user_id: i32,
+        expires_at: i64,
+    ) -> Result<()> {
+        let expires_at_naive = DateTime::from_timestamp(expires_at, 0)
+            .context("Token expiry out of range")?
+            .naive_utc();
+
+        let revoked = revoked_token_entity::ActiveModel {
+            jti: Set(jti.to_string()),
+            user_id: Set(user_id),
+            expires_at: Set(expires_at_naive),
+            ..Default::default()
+        };
+
+        revoked_token_entity::Entity::insert(revoked)
+            .on_conflict(
+                OnConflict::column(revoked_token_entity::Column::Jti)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .do_nothing()
+            .exec(db)
+            .await?;
+
+        cache.insert(jti.to_string(), expires_at);
+        Ok(())
+    }
+
+    /// Deletes expired revocations and pulls ones made by other instances into the cache.
+    pub async fn sync_revocations(
+        db: &DatabaseConnection,
+        cache: &RevocationCache,
+        access_token_ttl_minutes: i64,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        let access_token_ttl = chrono::Duration::minutes(access_token_ttl_minutes);
+
+        revoked_token_entity::Entity::delete_many()
+            .filter(revoked_token_entity::Column::ExpiresAt.lte(now))
+            .exec(db)
+            .await?;
+
+        let active = revoked_token_entity::Entity::find()
+            .filter(revoked_token_entity::Column::ExpiresAt.gt(now))
+            .all(db)
+            .await?;
+
+        cache.extend(
+            active
+                .into_iter()
+                .map(|revoked| (revoked.jti, revoked.expires_at.and_utc().timestamp())),
+        );
+        let revoked_sessions = user_entity::Entity::find()
+            .filter(user_entity::Column::SessionsRevokedAt.gt(now - access_token_ttl))
+            .all(db)
+            .await?;
+
+        for user in revoked_sessions {
+            if let Some(revoked_at) = user.sessions_revoked_at {
+                let revoked_at = revoked_at.and_utc();
+                cache.revoke_sessions_before(
+                    user.id,
+                    revoked_at.timestamp(),
+                    (revoked_at + access_token_ttl).timestamp(),
+                );
+            }
+        }
+
+        cache.prune_expired();
+
+        Ok(())
+    }
+
+    pub fn spawn_revocation_sync(
+        db: DatabaseConnection,
+        cache: RevocationCache,
+        access_token_ttl_minutes: i64,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = Self::sync_revocations(&db, &cache, access_token_ttl_minutes).await
+                {
+                    tracing::error!("Failed to sync revoked tokens: {e}");
+                }
+            }
+        })
+    }
+}
@@ -1,26 +1,47 @@
-use axum::{Json, extract::State, http::StatusCode};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use jsonwebtoken::jwk::JwkSet;
 use validator::Validate;
 
+use super::email_verification_service::EmailVerificationService;
+use super::mfa_service::MfaService;
+use super::password_reset_service::PasswordResetService;
+use super::refresh_token_service::RefreshTokenService;
+use super::revoked_token_service::RevokedTokenService;
 use super::user_dto::{
-    CreateUserPayload, CreateUserResponse, GetUsersResponse, LoginUserPayload, LoginUserResponse,
+    CreateUserPayload, CreateUserResponse, ForgotPasswordPayload, GetUsersResponse, LoginResponse,
+    LoginUserPayload, LoginUserResponse, LogoutPayload, MfaLoginPayload, MfaRequiredResponse,
+    RefreshTokenPayload, ResetPasswordPayload, UpdateUserRolePayload, VerifyEmailPayload,
 };
+use super::user_entity;
 use super::user_service::UserService;
 use crate::{
-    middleware::AuthClaims,
-    modules::shared::error::AppError,
+    middleware::{Admin, ClientIp, RequireRole, SessionClaims},
+    modules::shared::{
+        error::AppError,
+        pagination::{Page, PageQuery, PageRequest},
+    },
     state::AppState,
-    utils::{auth::create_token, hash},
+    utils::{
+        auth::{create_mfa_token, create_token, verify_mfa_token},
+        hash,
+        metrics::{self, LoginOutcome, LoginStep},
+    },
 };
 
 pub async fn me_handler(
     State(state): State<AppState>,
-    AuthClaims(claims): AuthClaims,
+    SessionClaims(claims): SessionClaims,
 ) -> Result<(StatusCode, Json<CreateUserResponse>), AppError> {
     let user_option = UserService::find_user_by_id(&state.db, claims.sub)
         .await
-        .map_err(|e| AppError::internal(e))?;
+        .map_err(AppError::internal)?;
 
-    if let None = user_option {
+    if user_option.is_none() {
         return Err(AppError::NotFound("User not found".to_string()));
     }
 
@@ -32,28 +53,60 @@ pub async fn me_handler(
             id: user.id,
             email: user.email,
             name: user.name,
+            role: user.role,
+            email_verified: user.email_verified_at.is_some(),
         }),
     ))
 }
 
 pub async fn find_all_users_handler(
     State(state): State<AppState>,
-    AuthClaims(_claims): AuthClaims,
-) -> Result<(StatusCode, Json<Vec<GetUsersResponse>>), AppError> {
-    let users = UserService::find_all_users(&state.db)
+    _admin: RequireRole<Admin>,
+    Query(query): Query<PageQuery>,
+) -> Result<(StatusCode, Json<Page<GetUsersResponse>>), AppError> {
+    let page = PageRequest::try_from(query)?;
+
+    let users = UserService::find_users_page(&state.db, page)
         .await
-        .map_err(|e| AppError::internal(e))?;
+        .map_err(AppError::internal)?;
+
+    let response = users.map(|user| GetUsersResponse {
+        id: user.id,
+        email: user.email,
+        name: user.name,
+        role: user.role,
+    });
 
-    let response: Vec<GetUsersResponse> = users
-        .into_iter()
-        .map(|user| GetUsersResponse {
+    Ok((StatusCode::OK, Json(response)))
+}
+
+pub async fn update_user_role_handler(
+    State(state): State<AppState>,
+    RequireRole(claims, _): RequireRole<Admin>,
+    Path(user_id): Path<i32>,
+    Json(payload): Json<UpdateUserRolePayload>,
+) -> Result<(StatusCode, Json<GetUsersResponse>), AppError> {
+    if user_id == claims.sub {
+        return Err(AppError::Forbidden(
+            "Admins cannot change their own role".to_string(),
+        ));
+    }
+
+    let user = UserService::update_user_role(&state.db, user_id, payload.role)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    revoke_cached_sessions(&state, &user);
+
+    Ok((
+        StatusCode::OK,
+        Json(GetUsersResponse {
             id: user.id,
             email: user.email,
             name: user.name,
-        })
-        .collect();
-
-    Ok((StatusCode::OK, Json(response)))
+            role: user.role,
+        }),
+    ))
 }
 
 pub async fn register_user_handler(
@@ -64,7 +117,27 @@ pub async fn register_user_handler(
 
     let user = UserService::create_user(&state.db, payload.email, payload.name, payload.password)
         .await
-        .map_err(|e| AppError::internal(e))?;
+        .map_err(AppError::internal)?;
+
+    let token = EmailVerificationService::issue_verification_token(
+        &state.db,
+        user.id,
+        state.account_config.email_verification_ttl_hours,
+    )
+    .await
+    .map_err(AppError::internal)?;
+
+    // The account exists either way; the user can ask for a new link if delivery fails.
+    if let Err(e) = EmailVerificationService::send_verification_email(
+        state.mailer.as_ref(),
+        &state.account_config,
+        &user.email,
+        &token,
+    )
+    .await
+    {
+        tracing::error!("Failed to send verification email to user {}: {e}", user.id);
+    }
 
     Ok((
         StatusCode::CREATED,
@@ -72,37 +145,376 @@ pub async fn register_user_handler(
             id: user.id,
             email: user.email,
             name: user.name,
+            role: user.role,
+            email_verified: user.email_verified_at.is_some(),
         }),
     ))
 }
 
 pub async fn login_user_handler(
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Json(payload): Json<LoginUserPayload>,
-) -> Result<(StatusCode, Json<LoginUserResponse>), AppError> {
+) -> Result<(StatusCode, Json<LoginResponse>), AppError> {
     payload.validate().map_err(AppError::validation)?;
 
+    let throttle = &state.login_throttle;
+    let client_ip = client_ip.as_deref();
+
+    if let Some(retry_after) = throttle
+        .reserve(&payload.email, client_ip)
+        .await
+        .map_err(AppError::internal)?
+    {
+        metrics::record_login(LoginStep::Password, LoginOutcome::Throttled);
+        return Err(AppError::TooManyRequests {
+            message: "Too many failed login attempts, try again later".to_string(),
+            retry_after: retry_after.unsigned_abs(),
+        });
+    }
+
     let user_option = UserService::find_user_by_email(&state.db, &payload.email)
         .await
-        .map_err(|e| AppError::internal(e))?;
+        .map_err(AppError::internal)?;
+
+    let verified = match &user_option {
+        Some(user) => {
+            hash::verify_password_blocking(payload.password.clone(), user.password.clone())
+                .await
+                .map_err(AppError::internal)?
+        }
+        None => false,
+    };
+
+    let user = match user_option {
+        Some(user) if verified => user,
+        // The attempt reserved above stays counted as the failure.
+        _ => {
+            metrics::record_login(LoginStep::Password, LoginOutcome::Failure);
+
+            return Err(AppError::Unauthorized(
+                "Invalid email or password".to_string(),
+            ));
+        }
+    };
+
+    if MfaService::is_totp_enabled(&state.db, user.id)
+        .await
+        .map_err(AppError::internal)?
+    {
+        let mfa = create_mfa_token(
+            &state.jwt_config,
+            user.id,
+            user.role,
+            state.account_config.mfa_token_ttl_minutes,
+        )
+        .map_err(AppError::internal)?;
+        throttle
+            .release_ip(client_ip)
+            .await
+            .map_err(AppError::internal)?;
+        metrics::record_login(LoginStep::Password, LoginOutcome::MfaRequired);
+
+        return Ok((
+            StatusCode::OK,
+            Json(LoginResponse::MfaRequired(MfaRequiredResponse {
+                mfa_required: true,
+                mfa_token: mfa.token,
+                mfa_token_expires_at: mfa.expires_at,
+            })),
+        ));
+    }
+
+    throttle
+        .record_success(&payload.email, client_ip)
+        .await
+        .map_err(AppError::internal)?;
+
+    let response = issue_login_tokens(&state, &user).await?;
+    metrics::record_login(LoginStep::Password, LoginOutcome::Success);
+
+    Ok((StatusCode::OK, Json(LoginResponse::Authenticated(response))))
+}
+
+pub async fn login_mfa_handler(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Json(payload): Json<MfaLoginPayload>,
+) -> Result<(StatusCode, Json<LoginUserResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let invalid_token = || AppError::Unauthorized("Invalid or expired MFA token".to_string());
+
+    let claims =
+        verify_mfa_token(&state.jwt_config, &payload.mfa_token).map_err(|_| invalid_token())?;
+
+    if state
+        .revoked_tokens
+        .is_session_revoked(claims.sub, claims.iat)
+    {
+        return Err(invalid_token());
+    }
+
+    let user = UserService::find_user_by_id(&state.db, claims.sub)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(invalid_token)?;
+
+    let throttle = &state.login_throttle;
+    let client_ip = client_ip.as_deref();
+
+    if let Some(retry_after) = throttle
+        .reserve(&user.email, client_ip)
+        .await
+        .map_err(AppError::internal)?
+    {
+        metrics::record_login(LoginStep::Mfa, LoginOutcome::Throttled);
+        return Err(AppError::TooManyRequests {
+            message: "Too many failed login attempts, try again later".to_string(),
+            retry_after: retry_after.unsigned_abs(),
+        });
+    }
+
+    if !MfaService::verify_second_factor(&state.db, user.id, &payload.code)
+        .await
+        .map_err(AppError::internal)?
+    {
+        metrics::record_login(LoginStep::Mfa, LoginOutcome::Failure);
 
-    if let None = user_option {
         return Err(AppError::Unauthorized(
-            "Invalid email or password".to_string(),
+            "Invalid authentication code".to_string(),
         ));
     }
 
-    let user = user_option.unwrap();
+    throttle
+        .record_success(&user.email, client_ip)
+        .await
+        .map_err(AppError::internal)?;
+
+    let response = issue_login_tokens(&state, &user).await?;
+    metrics::record_login(LoginStep::Mfa, LoginOutcome::Success);
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn issue_login_tokens(
+    state: &AppState,
+    user: &user_entity::Model,
+) -> Result<LoginUserResponse, AppError> {
+    let access = create_token(&state.jwt_config, user.id, user.role).map_err(AppError::internal)?;
+
+    let refresh = RefreshTokenService::issue_refresh_token(
+        &state.db,
+        user.id,
+        state.jwt_config.refresh_token_ttl_days,
+    )
+    .await
+    .map_err(AppError::internal)?;
 
-    let is_password_valid = hash::verify_password(&payload.password, &user.password);
+    Ok(LoginUserResponse {
+        access_token: access.token,
+        access_token_expires_at: access.expires_at,
+        refresh_token: refresh.token,
+        refresh_token_expires_at: refresh.expires_at,
+    })
+}
 
-    if !is_password_valid {
+pub async fn refresh_token_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshTokenPayload>,
+) -> Result<(StatusCode, Json<LoginUserResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let rotated = RefreshTokenService::rotate_refresh_token(
+        &state.db,
+        &payload.refresh_token,
+        state.jwt_config.refresh_token_ttl_days,
+    )
+    .await
+    .map_err(AppError::internal)?;
+
+    let Some((user_id, refresh)) = rotated else {
         return Err(AppError::Unauthorized(
-            "Invalid email or password".to_string(),
+            "Invalid or expired refresh token".to_string(),
         ));
+    };
+
+    let user = UserService::find_user_by_id(&state.db, user_id)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::Unauthorized("Invalid or expired refresh token".to_string()))?;
+
+    let access = create_token(&state.jwt_config, user.id, user.role).map_err(AppError::internal)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(LoginUserResponse {
+            access_token: access.token,
+            access_token_expires_at: access.expires_at,
+            refresh_token: refresh.token,
+            refresh_token_expires_at: refresh.expires_at,
+        }),
+    ))
+}
+
+pub async fn logout_handler(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    payload: Option<Json<LogoutPayload>>,
+) -> Result<StatusCode, AppError> {
+    if let Some(Json(payload)) = payload {
+        payload.validate().map_err(AppError::validation)?;
+
+        if let Some(refresh_token) = payload.refresh_token {
+            RefreshTokenService::revoke_refresh_token(&state.db, claims.sub, &refresh_token)
+                .await
+                .map_err(AppError::internal)?;
+        }
+    }
+
+    RevokedTokenService::revoke_token(
+        &state.db,
+        &state.revoked_tokens,
+        &claims.jti,
+        claims.sub,
+        claims.exp,
+    )
+    .await
+    .map_err(AppError::internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn verify_email_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailPayload>,
+) -> Result<(StatusCode, Json<CreateUserResponse>), AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let user = EmailVerificationService::verify_email(&state.db, &payload.token)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("Invalid or expired verification token".to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        Json(CreateUserResponse {
+            id: user.id,
+            email: user.email,
+            name: user.name,
+            role: user.role,
+            email_verified: user.email_verified_at.is_some(),
+        }),
+    ))
+}
+
+pub async fn resend_verification_email_handler(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+) -> Result<StatusCode, AppError> {
+    let user = UserService::find_user_by_id(&state.db, claims.sub)
+        .await
+        .map_err(AppError::internal)?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    if user.email_verified_at.is_some() {
+        return Err(AppError::Conflict("Email is already verified".to_string()));
     }
 
-    let token = create_token(&state.jwt_config, user.id).map_err(AppError::internal)?;
+    let token = EmailVerificationService::issue_verification_token(
+        &state.db,
+        user.id,
+        state.account_config.email_verification_ttl_hours,
+    )
+    .await
+    .map_err(AppError::internal)?;
 
-    Ok((StatusCode::OK, Json(LoginUserResponse { token })))
+    EmailVerificationService::send_verification_email(
+        state.mailer.as_ref(),
+        &state.account_config,
+        &user.email,
+        &token,
+    )
+    .await
+    .map_err(AppError::internal)?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn forgot_password_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<ForgotPasswordPayload>,
+) -> Result<StatusCode, AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let user_option = UserService::find_user_by_email(&state.db, &payload.email)
+        .await
+        .map_err(AppError::internal)?;
+
+    // Respond the same way whether or not the account exists.
+    if let Some(user) = user_option {
+        let token = PasswordResetService::issue_reset_token(
+            &state.db,
+            user.id,
+            state.account_config.password_reset_ttl_minutes,
+        )
+        .await
+        .map_err(AppError::internal)?;
+
+        if let Err(e) = PasswordResetService::send_reset_email(
+            state.mailer.as_ref(),
+            &state.account_config,
+            &user.email,
+            &token,
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to send password reset email to user {}: {e}",
+                user.id
+            );
+        }
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn reset_password_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<ResetPasswordPayload>,
+) -> Result<StatusCode, AppError> {
+    payload.validate().map_err(AppError::validation)?;
+
+    let user =
+        PasswordResetService::reset_password(&state.db, &payload.token, &payload.new_password)
+            .await
+            .map_err(AppError::internal)?
+            .ok_or_else(|| AppError::NotFound("Invalid or expired reset token".to_string()))?;
+
+    revoke_cached_sessions(&state, &user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Applies the user's `sessions_revoked_at` to the in-memory cache right away,
+/// rather than waiting for the next revocation sync.
+fn revoke_cached_sessions(state: &AppState, user: &user_entity::Model) {
+    if let Some(revoked_at) = user.sessions_revoked_at {
+        let revoked_at = revoked_at.and_utc().timestamp();
+        state.revoked_tokens.revoke_sessions_before(
+            user.id,
+            revoked_at,
+            revoked_at + state.jwt_config.access_token_ttl_minutes * 60,
+        );
+    }
+}
+
+pub async fn jwks_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let jwks: JwkSet = state.jwt_config.jwks();
+
+    (
+        StatusCode::OK,
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(jwks),
+    )
 }
@@ -1,11 +1,17 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use super::{
+    api_key_entity::{self, ApiScope},
+    user_entity::UserRole,
+};
+
 #[derive(Debug, Serialize)]
 pub struct GetUsersResponse {
     pub id: i32,
     pub email: String,
     pub name: Option<String>,
+    pub role: UserRole,
 }
 
 #[derive(Debug, Validate, Deserialize)]
@@ -25,6 +31,8 @@ pub struct CreateUserResponse {
     pub id: i32,
     pub email: String,
     pub name: Option<String>,
+    pub role: UserRole,
+    pub email_verified: bool,
 }
 
 #[derive(Debug, Validate, Deserialize)]
@@ -38,5 +46,130 @@ pub struct LoginUserPayload {
 
 #[derive(Debug, Serialize)]
 pub struct LoginUserResponse {
+    pub access_token: String,
+    pub access_token_expires_at: i64,
+    pub refresh_token: String,
+    pub refresh_token_expires_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MfaRequiredResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub mfa_token_expires_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(LoginUserResponse),
+    MfaRequired(MfaRequiredResponse),
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct MfaLoginPayload {
+    #[validate(length(min = 1))]
+    pub mfa_token: String,
+
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct RefreshTokenPayload {
+    #[validate(length(min = 1))]
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct LogoutPayload {
+    #[validate(length(min = 1))]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserRolePayload {
+    pub role: UserRole,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct VerifyEmailPayload {
+    #[validate(length(min = 1))]
+    pub token: String,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct ForgotPasswordPayload {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct ResetPasswordPayload {
+    #[validate(length(min = 1))]
     pub token: String,
+
+    #[validate(length(min = 6, max = 100))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct CreateApiKeyPayload {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    #[validate(length(min = 1))]
+    pub scopes: Vec<ApiScope>,
+
+    #[validate(range(min = 1, max = 365))]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: i32,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<ApiScope>,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<api_key_entity::Model> for ApiKeyResponse {
+    fn from(api_key: api_key_entity::Model) -> Self {
+        ApiKeyResponse {
+            scopes: api_key.api_scopes(),
+            id: api_key.id,
+            name: api_key.name,
+            prefix: api_key.prefix,
+            expires_at: api_key.expires_at.map(|t| t.to_string()),
+            last_used_at: api_key.last_used_at.map(|t| t.to_string()),
+            created_at: api_key.created_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+    pub key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TotpEnrollmentResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct MfaCodePayload {
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
 }
@@ -12,6 +12,32 @@ pub struct Model {
     pub email: String,
     pub name: Option<String>,
     pub password: String,
+    pub role: UserRole,
+    pub email_verified_at: Option<DateTime>,
+    pub sessions_revoked_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "user_role")]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    #[default]
+    #[sea_orm(string_value = "user")]
+    User,
+    #[sea_orm(string_value = "admin")]
+    Admin,
+}
+
+impl UserRole {
+    pub fn grants(self, required: UserRole) -> bool {
+        match self {
+            UserRole::Admin => true,
+            UserRole::User => required == UserRole::User,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
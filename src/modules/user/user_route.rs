@@ -1,16 +1,52 @@
 use axum::{
     Router,
-    routing::{get, post},
+    routing::{delete, get, patch, post},
 };
 
+use super::api_key_controller::{
+    create_api_key_handler, find_api_keys_handler, revoke_api_key_handler,
+};
+use super::mfa_controller::{
+    confirm_totp_handler, disable_totp_handler, enroll_totp_handler,
+    regenerate_recovery_codes_handler,
+};
 use super::user_controller::register_user_handler;
-use super::user_controller::{find_all_users_handler, login_user_handler, me_handler};
+use super::user_controller::{
+    find_all_users_handler, forgot_password_handler, login_mfa_handler, login_user_handler,
+    logout_handler, me_handler, refresh_token_handler, resend_verification_email_handler,
+    reset_password_handler, update_user_role_handler, verify_email_handler,
+};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", get(find_all_users_handler))
         .route("/me", get(me_handler))
+        .route(
+            "/me/api-keys",
+            get(find_api_keys_handler).post(create_api_key_handler),
+        )
+        .route("/me/api-keys/{id}", delete(revoke_api_key_handler))
+        .route(
+            "/me/mfa/totp",
+            post(enroll_totp_handler).delete(disable_totp_handler),
+        )
+        .route("/me/mfa/totp/confirm", post(confirm_totp_handler))
+        .route(
+            "/me/mfa/recovery-codes",
+            post(regenerate_recovery_codes_handler),
+        )
+        .route("/{id}/role", patch(update_user_role_handler))
         .route("/", post(register_user_handler))
         .route("/login", post(login_user_handler))
+        .route("/login/mfa", post(login_mfa_handler))
+        .route("/logout", post(logout_handler))
+        .route("/token/refresh", post(refresh_token_handler))
+        .route("/password/forgot", post(forgot_password_handler))
+        .route("/password/reset", post(reset_password_handler))
+        .route("/verify-email", post(verify_email_handler))
+        .route(
+            "/verify-email/resend",
+            post(resend_verification_email_handler),
+        )
 }
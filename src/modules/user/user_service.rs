@@ -1,13 +1,22 @@
 use anyhow::Result;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait,
+};
+use tracing::instrument;
 
-use super::user_entity;
-use crate::utils::hash::hash_password;
+use super::{refresh_token_service::RefreshTokenService, user_entity};
+use crate::{
+    modules::shared::pagination::{Cursor, Page, PageRequest},
+    utils::hash::hash_password,
+};
 
 #[derive(Clone)]
 pub struct UserService;
 
 impl UserService {
+    #[instrument(skip_all)]
     pub async fn create_user(
         db: &DatabaseConnection,
         email: String,
@@ -27,11 +36,27 @@ impl UserService {
         Ok(inserted)
     }
 
-    pub async fn find_all_users(db: &DatabaseConnection) -> Result<Vec<user_entity::Model>> {
-        let users = user_entity::Entity::find().all(db).await?;
-        Ok(users)
+    #[instrument(skip_all)]
+    pub async fn find_users_page(
+        db: &DatabaseConnection,
+        page: PageRequest,
+    ) -> Result<Page<user_entity::Model>> {
+        let users = page
+            .apply(
+                user_entity::Entity::find(),
+                user_entity::Column::CreatedAt,
+                user_entity::Column::Id,
+            )
+            .all(db)
+            .await?;
+
+        Ok(page.into_page(users, |user| Cursor {
+            created_at: user.created_at,
+            id: user.id,
+        }))
     }
 
+    #[instrument(skip(db))]
     pub async fn find_user_by_id(
         db: &DatabaseConnection,
         user_id: i32,
@@ -40,6 +65,39 @@ impl UserService {
         Ok(user)
     }
 
+    /// Changes the user's role. A real change also revokes every session of
+    /// the user, so tokens carrying the old role stop working.
+    #[instrument(skip(db))]
+    pub async fn update_user_role(
+        db: &DatabaseConnection,
+        user_id: i32,
+        role: user_entity::UserRole,
+    ) -> Result<Option<user_entity::Model>> {
+        let txn = db.begin().await?;
+
+        let Some(user) = user_entity::Entity::find_by_id(user_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if user.role == role {
+            return Ok(Some(user));
+        }
+
+        let mut active_model: user_entity::ActiveModel = user.into();
+        active_model.role = Set(role);
+        active_model.sessions_revoked_at = Set(Some(Utc::now().naive_utc()));
+        let updated_user = active_model.update(&txn).await?;
+
+        RefreshTokenService::revoke_all_for_user(&txn, user_id).await?;
+
+        txn.commit().await?;
+        Ok(Some(updated_user))
+    }
+
+    #[instrument(skip_all)]
     pub async fn find_user_by_email(
         db: &DatabaseConnection,
         email: &str,
@@ -1,9 +1,28 @@
+use std::sync::Arc;
+
 use sea_orm::DatabaseConnection;
 
-use crate::utils::auth::JwtConfig;
+use crate::{
+    config::{account::AccountConfig, product::ProductConfig, search::SearchConfig},
+    mailer::Mailer,
+    storage::Storage,
+    utils::{
+        api_key_cache::ApiKeyCache, auth::JwtConfig, revocation::RevocationCache,
+        shutdown::DrainState, throttle::LoginThrottle,
+    },
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: DatabaseConnection,
     pub jwt_config: JwtConfig,
+    pub revoked_tokens: RevocationCache,
+    pub api_keys: ApiKeyCache,
+    pub account_config: AccountConfig,
+    pub mailer: Arc<dyn Mailer>,
+    pub login_throttle: LoginThrottle,
+    pub search_config: SearchConfig,
+    pub product_config: ProductConfig,
+    pub storage: Arc<dyn Storage>,
+    pub drain: DrainState,
 }
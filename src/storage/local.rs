@@ -0,0 +1,122 @@
+use std::{
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use axum::body::Bytes;
+use uuid::Uuid;
+
+use super::{Storage, StoredObject};
+
+/// Stores objects as files below a root directory.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalStorage { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+
+        if key.is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!("Invalid storage key `{key}`");
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, bytes: Bytes) -> Result<()> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Write to a sibling temp file first so readers never see a partial object.
+        let temp = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+        tokio::fs::write(&temp, &bytes).await?;
+        if let Err(e) = tokio::fs::rename(&temp, &path).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>> {
+        let path = self.path_for(key)?;
+
+        let file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let size = file.metadata().await?.len();
+
+        Ok(Some(StoredObject {
+            size,
+            reader: Box::pin(file),
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path_for(key)?;
+
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_storage_round_trip() {
+        let dir = std::env::temp_dir().join(format!("storage-test-{}", Uuid::new_v4()));
+        let storage = LocalStorage::new(&dir);
+
+        storage
+            .put("products/1/image", Bytes::from_static(b"hello"))
+            .await
+            .unwrap();
+
+        let mut object = storage.get("products/1/image").await.unwrap().unwrap();
+        let mut contents = Vec::new();
+        object.reader.read_to_end(&mut contents).await.unwrap();
+
+        assert_eq!(object.size, 5);
+        assert_eq!(contents, b"hello");
+
+        storage.delete("products/1/image").await.unwrap();
+        assert!(storage.get("products/1/image").await.unwrap().is_none());
+        storage.delete("products/1/image").await.unwrap();
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_rejects_escaping_keys() {
+        let storage = LocalStorage::new(std::env::temp_dir());
+
+        for key in ["", "../etc/passwd", "/etc/passwd", "a/../b"] {
+            assert!(storage.get(key).await.is_err(), "{key}");
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use std::pin::Pin;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::body::Bytes;
+use tokio::io::AsyncRead;
+
+pub mod local;
+
+pub struct StoredObject {
+    pub size: u64,
+    pub reader: Pin<Box<dyn AsyncRead + Send>>,
+}
+
+/// Blob store for uploaded files, addressed by `/`-separated keys.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, bytes: Bytes) -> Result<()>;
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>>;
+    async fn delete(&self, key: &str) -> Result<()>;
+}
@@ -0,0 +1,78 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::utils::token::hash_token;
+
+/// How long a verified secret is trusted without re-running argon2.
+const VERIFIED_TTL: Duration = Duration::from_secs(60);
+
+/// Entries kept at most. Only keys that verified get in, so this bounds the
+/// number of distinct valid keys in use rather than anything a caller picks.
+const MAX_ENTRIES: usize = 10_000;
+
+struct VerifiedSecret {
+    secret_digest: String,
+    secret_hash: String,
+    verified_at: Instant,
+}
+
+/// Recently verified API key secrets, keyed by key prefix, so a busy key
+/// costs one argon2 verification per `VERIFIED_TTL` instead of one per
+/// request. Revocation and expiry are still read from the database on every
+/// request; a hit only skips the hash.
+#[derive(Clone, Default)]
+pub struct ApiKeyCache {
+    entries: Arc<Mutex<HashMap<String, VerifiedSecret>>>,
+}
+
+impl ApiKeyCache {
+    /// Whether `secret` was verified against `secret_hash` within the TTL.
+    /// A rotated hash or a different secret misses.
+    pub fn is_verified(&self, prefix: &str, secret: &str, secret_hash: &str) -> bool {
+        let entries = self.entries.lock().expect("api key cache poisoned");
+        entries.get(prefix).is_some_and(|entry| {
+            entry.verified_at.elapsed() < VERIFIED_TTL
+                && entry.secret_hash == secret_hash
+                && entry.secret_digest == hash_token(secret)
+        })
+    }
+
+    pub fn insert(&self, prefix: &str, secret: &str, secret_hash: &str) {
+        let mut entries = self.entries.lock().expect("api key cache poisoned");
+
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(prefix) {
+            entries.retain(|_, entry| entry.verified_at.elapsed() < VERIFIED_TTL);
+            if entries.len() >= MAX_ENTRIES {
+                return;
+            }
+        }
+
+        entries.insert(
+            prefix.to_string(),
+            VerifiedSecret {
+                secret_digest: hash_token(secret),
+                secret_hash: secret_hash.to_string(),
+                verified_at: Instant::now(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_verified_secret_and_hash_hit() {
+        let cache = ApiKeyCache::default();
+        cache.insert("abc", "secret", "$argon2id$one");
+
+        assert!(cache.is_verified("abc", "secret", "$argon2id$one"));
+        assert!(!cache.is_verified("abc", "guess", "$argon2id$one"));
+        assert!(!cache.is_verified("abc", "secret", "$argon2id$two"));
+        assert!(!cache.is_verified("def", "secret", "$argon2id$one"));
+    }
+}
@@ -1,93 +1,251 @@
+use anyhow::Context;
 use chrono::{Duration, Utc};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::{Header, Validation, decode, decode_header, encode, jwk::JwkSet};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::jwt_key::JwtKey;
+use crate::modules::user::{api_key_entity::ApiScope, user_entity::UserRole};
+
+pub const MFA_AUDIENCE: &str = "mfa";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: i32,
+    pub jti: String,
+    #[serde(default)]
+    pub role: UserRole,
+    /// Set only for API key requests; JWT sessions are not scope-limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<ApiScope>>,
     pub exp: i64,
     pub iat: i64,
     pub iss: String,
     pub aud: Option<String>,
 }
 
+impl Claims {
+    pub fn is_api_key(&self) -> bool {
+        self.scopes.is_some()
+    }
+
+    pub fn has_scope(&self, scope: ApiScope) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.contains(&scope))
+    }
+}
+
 #[derive(Clone)]
 pub struct JwtConfig {
-    pub secret: String,
+    pub keys: Vec<JwtKey>,
+    pub signing_kid: String,
     pub issuer: String,
     pub access_token_ttl_minutes: i64,
+    pub refresh_token_ttl_days: i64,
+}
+
+pub struct AccessToken {
+    pub token: String,
+    pub expires_at: i64,
 }
 
 impl JwtConfig {
-    pub fn encoding_key(&self) -> EncodingKey {
-        EncodingKey::from_secret(self.secret.as_bytes())
+    pub fn find_key(&self, kid: &str) -> Option<&JwtKey> {
+        self.keys.iter().find(|key| key.kid == kid)
     }
 
-    pub fn decoding_key(&self) -> DecodingKey {
-        DecodingKey::from_secret(self.secret.as_bytes())
+    pub fn signing_key(&self) -> anyhow::Result<&JwtKey> {
+        self.find_key(&self.signing_kid)
+            .filter(|key| key.encoding_key().is_some())
+            .with_context(|| format!("No private key for signing kid `{}`", self.signing_kid))
     }
 
-    pub fn validation(&self) -> Validation {
-        let mut validation = Validation::new(Algorithm::HS256);
+    pub fn validation(&self, key: &JwtKey) -> Validation {
+        let mut validation = Validation::new(key.algorithm);
         validation.validate_exp = true;
         validation.leeway = 0;
-        validation.set_issuer(&[self.issuer.clone()]);
+        validation.set_issuer(std::slice::from_ref(&self.issuer));
         validation
     }
+
+    pub fn encode_claims(&self, claims: &Claims) -> anyhow::Result<String> {
+        let key = self.signing_key()?;
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+
+        let encoding_key = key
+            .encoding_key()
+            .context("Signing key has no private part")?;
+        let token = encode(&header, claims, encoding_key)?;
+        Ok(token)
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .keys
+                .iter()
+                .filter_map(|key| key.public_jwk().cloned())
+                .collect(),
+        }
+    }
 }
 
-pub fn create_token(cfg: &JwtConfig, subject: i32) -> anyhow::Result<String> {
+pub fn create_token(cfg: &JwtConfig, subject: i32, role: UserRole) -> anyhow::Result<AccessToken> {
     let now = Utc::now();
     let exp = now + Duration::minutes(cfg.access_token_ttl_minutes);
     let claims = Claims {
         sub: subject,
+        jti: Uuid::new_v4().to_string(),
+        role,
+        scopes: None,
         exp: exp.timestamp(),
         iat: now.timestamp(),
         iss: cfg.issuer.clone(),
         aud: None,
     };
 
-    let token = encode(&Header::new(Algorithm::HS256), &claims, &cfg.encoding_key())?;
-    Ok(token)
+    let token = cfg.encode_claims(&claims)?;
+    Ok(AccessToken {
+        token,
+        expires_at: claims.exp,
+    })
+}
+
+/// Issues the short-lived token handed out after a correct password when the
+/// account has two-factor authentication enabled. Its `aud` makes
+/// `verify_token` reject it, so it cannot be used as an access token.
+pub fn create_mfa_token(
+    cfg: &JwtConfig,
+    subject: i32,
+    role: UserRole,
+    ttl_minutes: i64,
+) -> anyhow::Result<AccessToken> {
+    let now = Utc::now();
+    let exp = now + Duration::minutes(ttl_minutes);
+    let claims = Claims {
+        sub: subject,
+        jti: Uuid::new_v4().to_string(),
+        role,
+        scopes: None,
+        exp: exp.timestamp(),
+        iat: now.timestamp(),
+        iss: cfg.issuer.clone(),
+        aud: Some(MFA_AUDIENCE.to_string()),
+    };
+
+    let token = cfg.encode_claims(&claims)?;
+    Ok(AccessToken {
+        token,
+        expires_at: claims.exp,
+    })
+}
+
+fn verifying_key<'a>(cfg: &'a JwtConfig, token: &str) -> anyhow::Result<&'a JwtKey> {
+    let header = decode_header(token)?;
+
+    // Tokens minted before `kid` was introduced carry no header hint.
+    match header.kid.as_deref() {
+        Some(kid) => cfg.find_key(kid).context("Unknown signing key"),
+        None => cfg.signing_key(),
+    }
 }
 
 pub fn verify_token(cfg: &JwtConfig, token: &str) -> anyhow::Result<Claims> {
-    let token_data = decode::<Claims>(token, &cfg.decoding_key(), &cfg.validation())?;
+    let key = verifying_key(cfg, token)?;
+
+    let token_data = decode::<Claims>(token, key.decoding_key(), &cfg.validation(key))?;
+    Ok(token_data.claims)
+}
+
+pub fn verify_mfa_token(cfg: &JwtConfig, token: &str) -> anyhow::Result<Claims> {
+    let key = verifying_key(cfg, token)?;
+
+    let mut validation = cfg.validation(key);
+    validation.set_audience(&[MFA_AUDIENCE]);
+    validation.set_required_spec_claims(&["exp", "aud"]);
+
+    let token_data = decode::<Claims>(token, key.decoding_key(), &validation)?;
     Ok(token_data.claims)
 }
 
 #[cfg(test)]
 mod tests {
+    use jsonwebtoken::Algorithm;
+
     use super::*;
 
-    fn cfg() -> JwtConfig {
+    const RSA_PRIVATE_PEM: &str = include_str!("testdata/rsa_private.pem");
+    const RSA_PUBLIC_PEM: &str = include_str!("testdata/rsa_public.pem");
+    const ED25519_PRIVATE_PEM: &str = include_str!("testdata/ed25519_private.pem");
+    const ED25519_PUBLIC_PEM: &str = include_str!("testdata/ed25519_public.pem");
+
+    fn cfg_with(keys: Vec<JwtKey>, signing_kid: &str) -> JwtConfig {
         JwtConfig {
-            secret: "super-secret-for-tests-change-in-prod".to_string(),
+            keys,
+            signing_kid: signing_kid.to_string(),
             issuer: "test-issuer".to_string(),
             access_token_ttl_minutes: 1,
+            refresh_token_ttl_days: 1,
         }
     }
 
+    fn cfg() -> JwtConfig {
+        cfg_with(
+            vec![JwtKey::hmac(
+                "default",
+                b"super-secret-for-tests-change-in-prod",
+            )],
+            "default",
+        )
+    }
+
+    fn rsa_key(kid: &str) -> JwtKey {
+        JwtKey::rsa_from_pem(kid, RSA_PUBLIC_PEM, Some(RSA_PRIVATE_PEM)).unwrap()
+    }
+
+    fn ed25519_key(kid: &str) -> JwtKey {
+        JwtKey::ed25519_from_pem(kid, ED25519_PUBLIC_PEM, Some(ED25519_PRIVATE_PEM)).unwrap()
+    }
+
     #[test]
     fn test_create_and_verify_token() {
         let c = cfg();
-        let token = create_token(&c, 123).unwrap();
-        let claims = verify_token(&c, &token).unwrap();
+        let access = create_token(&c, 123, UserRole::User).unwrap();
+        let claims = verify_token(&c, &access.token).unwrap();
         assert_eq!(claims.sub, 123);
+        assert_eq!(claims.role, UserRole::User);
         assert_eq!(claims.iss, "test-issuer");
         assert!(claims.exp > claims.iat);
+        assert_eq!(access.expires_at, claims.exp);
+    }
+
+    #[test]
+    fn test_create_token_assigns_unique_jti() {
+        let c = cfg();
+        let a = verify_token(&c, &create_token(&c, 123, UserRole::User).unwrap().token).unwrap();
+        let b = verify_token(&c, &create_token(&c, 123, UserRole::User).unwrap().token).unwrap();
+        assert_ne!(a.jti, b.jti);
+    }
+
+    #[test]
+    fn test_create_token_embeds_role() {
+        let c = cfg();
+        let access = create_token(&c, 1, UserRole::Admin).unwrap();
+        let claims = verify_token(&c, &access.token).unwrap();
+        assert_eq!(claims.role, UserRole::Admin);
     }
 
     #[test]
     fn test_verify_token_fails_with_wrong_secret() {
         let c1 = cfg();
-        let token = create_token(&c1, 123).unwrap();
+        let token = create_token(&c1, 123, UserRole::User).unwrap().token;
 
-        let c2 = JwtConfig {
-            secret: "different-secret".to_string(),
-            issuer: "test-issuer".to_string(),
-            access_token_ttl_minutes: 1,
-        };
+        let c2 = cfg_with(
+            vec![JwtKey::hmac("default", b"different-secret")],
+            "default",
+        );
 
         assert!(verify_token(&c2, &token).is_err());
     }
@@ -98,15 +256,98 @@ mod tests {
         let now = Utc::now();
         let claims = Claims {
             sub: 123,
+            jti: Uuid::new_v4().to_string(),
+            role: UserRole::User,
+            scopes: None,
             iat: now.timestamp(),
             exp: (now - Duration::seconds(1)).timestamp(),
             iss: c.issuer.clone(),
             aud: None,
         };
 
-        let token = encode(&Header::new(Algorithm::HS256), &claims, &c.encoding_key()).unwrap();
+        let token = c.encode_claims(&claims).unwrap();
 
         let res = verify_token(&c, &token);
         assert!(res.is_err());
     }
+
+    #[test]
+    fn test_mfa_token_is_not_an_access_token() {
+        let c = cfg();
+        let mfa = create_mfa_token(&c, 123, UserRole::User, 5).unwrap();
+        let access = create_token(&c, 123, UserRole::User).unwrap();
+
+        assert!(verify_token(&c, &mfa.token).is_err());
+        assert!(verify_mfa_token(&c, &access.token).is_err());
+        assert_eq!(verify_mfa_token(&c, &mfa.token).unwrap().sub, 123);
+    }
+
+    #[test]
+    fn test_create_and_verify_token_with_asymmetric_keys() {
+        for key in [rsa_key("rsa-1"), ed25519_key("ed-1")] {
+            let kid = key.kid.clone();
+            let algorithm = key.algorithm;
+            let c = cfg_with(vec![key], &kid);
+
+            let token = create_token(&c, 7, UserRole::User).unwrap().token;
+            let header = decode_header(&token).unwrap();
+
+            assert_eq!(header.kid.as_deref(), Some(kid.as_str()));
+            assert_eq!(header.alg, algorithm);
+            assert_eq!(verify_token(&c, &token).unwrap().sub, 7);
+        }
+    }
+
+    #[test]
+    fn test_verify_token_accepts_retired_key_after_rotation() {
+        let old = cfg_with(vec![ed25519_key("2025-01")], "2025-01");
+        let token = create_token(&old, 7, UserRole::User).unwrap().token;
+
+        let retired =
+            JwtKey::from_pem("2025-01", Algorithm::EdDSA, ED25519_PUBLIC_PEM, None).unwrap();
+        let rotated = cfg_with(vec![rsa_key("2025-06"), retired], "2025-06");
+
+        assert_eq!(verify_token(&rotated, &token).unwrap().sub, 7);
+        assert!(create_token(&rotated, 7, UserRole::User).is_ok());
+    }
+
+    #[test]
+    fn test_verify_token_fails_with_unknown_kid() {
+        let token = create_token(&cfg_with(vec![rsa_key("a")], "a"), 7, UserRole::User)
+            .unwrap()
+            .token;
+
+        let c = cfg_with(vec![rsa_key("b")], "b");
+
+        assert!(verify_token(&c, &token).is_err());
+    }
+
+    #[test]
+    fn test_signing_key_requires_private_part() {
+        let public_only = JwtKey::from_pem("pub", Algorithm::RS256, RSA_PUBLIC_PEM, None).unwrap();
+        let c = cfg_with(vec![public_only], "pub");
+
+        assert!(create_token(&c, 7, UserRole::User).is_err());
+    }
+
+    #[test]
+    fn test_jwks_publishes_only_public_keys() {
+        let c = cfg_with(
+            vec![
+                JwtKey::hmac("hs", b"secret"),
+                rsa_key("rsa-1"),
+                ed25519_key("ed-1"),
+            ],
+            "rsa-1",
+        );
+
+        let kids: Vec<_> = c
+            .jwks()
+            .keys
+            .into_iter()
+            .filter_map(|jwk| jwk.common.key_id)
+            .collect();
+
+        assert_eq!(kids, vec!["rsa-1".to_string(), "ed-1".to_string()]);
+    }
 }
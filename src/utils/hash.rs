@@ -1,3 +1,4 @@
+use anyhow::Result;
 use argon2::{Argon2, PasswordHasher, PasswordVerifier};
 use password_hash::{PasswordHash, SaltString, rand_core::OsRng};
 
@@ -22,6 +23,17 @@ pub fn verify_password(password: &str, hash_phc: &str) -> bool {
         .is_ok()
 }
 
+/// `verify_password` on the blocking pool, since argon2 is deliberately slow
+/// and would otherwise stall the runtime under a burst of logins.
+pub async fn verify_password_blocking(password: String, hash_phc: String) -> Result<bool> {
+    Ok(tokio::task::spawn_blocking(move || verify_password(&password, &hash_phc)).await?)
+}
+
+/// `hash_password` on the blocking pool, for the same reason.
+pub async fn hash_password_blocking(password: String) -> Result<String> {
+    Ok(tokio::task::spawn_blocking(move || hash_password(&password)).await?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
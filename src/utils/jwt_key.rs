@@ -0,0 +1,138 @@
+use anyhow::{Context, Result, bail};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ed25519_dalek::VerifyingKey;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+};
+use rsa::{
+    RsaPublicKey, pkcs1::DecodeRsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts,
+};
+
+#[derive(Clone)]
+pub struct JwtKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    encoding_key: Option<EncodingKey>,
+    decoding_key: DecodingKey,
+    public_jwk: Option<Jwk>,
+}
+
+impl JwtKey {
+    pub fn hmac(kid: impl Into<String>, secret: &[u8]) -> Self {
+        JwtKey {
+            kid: kid.into(),
+            algorithm: Algorithm::HS256,
+            encoding_key: Some(EncodingKey::from_secret(secret)),
+            decoding_key: DecodingKey::from_secret(secret),
+            public_jwk: None,
+        }
+    }
+
+    /// Builds an RS256 key. Without a private key it can only verify tokens,
+    /// which is how retired keys stay valid until their tokens expire.
+    pub fn rsa_from_pem(
+        kid: impl Into<String>,
+        public_pem: &str,
+        private_pem: Option<&str>,
+    ) -> Result<Self> {
+        let kid = kid.into();
+
+        let public_key = RsaPublicKey::from_public_key_pem(public_pem)
+            .or_else(|_| RsaPublicKey::from_pkcs1_pem(public_pem))
+            .with_context(|| format!("Invalid RSA public key for kid `{kid}`"))?;
+
+        let encoding_key = private_pem
+            .map(|pem| EncodingKey::from_rsa_pem(pem.as_bytes()))
+            .transpose()
+            .with_context(|| format!("Invalid RSA private key for kid `{kid}`"))?;
+
+        let public_jwk = Jwk {
+            common: public_common_parameters(&kid, KeyAlgorithm::RS256),
+            algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+                e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+            }),
+        };
+
+        Ok(JwtKey {
+            decoding_key: DecodingKey::from_rsa_pem(public_pem.as_bytes())?,
+            kid,
+            algorithm: Algorithm::RS256,
+            encoding_key,
+            public_jwk: Some(public_jwk),
+        })
+    }
+
+    pub fn ed25519_from_pem(
+        kid: impl Into<String>,
+        public_pem: &str,
+        private_pem: Option<&str>,
+    ) -> Result<Self> {
+        let kid = kid.into();
+
+        let public_key = VerifyingKey::from_public_key_pem(public_pem)
+            .with_context(|| format!("Invalid Ed25519 public key for kid `{kid}`"))?;
+
+        let encoding_key = private_pem
+            .map(|pem| EncodingKey::from_ed_pem(pem.as_bytes()))
+            .transpose()
+            .with_context(|| format!("Invalid Ed25519 private key for kid `{kid}`"))?;
+
+        let public_jwk = Jwk {
+            common: public_common_parameters(&kid, KeyAlgorithm::EdDSA),
+            algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(public_key.as_bytes()),
+            }),
+        };
+
+        Ok(JwtKey {
+            decoding_key: DecodingKey::from_ed_pem(public_pem.as_bytes())?,
+            kid,
+            algorithm: Algorithm::EdDSA,
+            encoding_key,
+            public_jwk: Some(public_jwk),
+        })
+    }
+
+    pub fn from_pem(
+        kid: impl Into<String>,
+        algorithm: Algorithm,
+        public_pem: &str,
+        private_pem: Option<&str>,
+    ) -> Result<Self> {
+        match algorithm {
+            Algorithm::RS256 => Self::rsa_from_pem(kid, public_pem, private_pem),
+            Algorithm::EdDSA => Self::ed25519_from_pem(kid, public_pem, private_pem),
+            other => bail!("Unsupported JWT key algorithm: {other:?}"),
+        }
+    }
+
+    pub fn encoding_key(&self) -> Option<&EncodingKey> {
+        self.encoding_key.as_ref()
+    }
+
+    pub fn decoding_key(&self) -> &DecodingKey {
+        &self.decoding_key
+    }
+
+    /// Public JWK for asymmetric keys; shared secrets are never published.
+    pub fn public_jwk(&self) -> Option<&Jwk> {
+        self.public_jwk.as_ref()
+    }
+}
+
+fn public_common_parameters(kid: &str, algorithm: KeyAlgorithm) -> CommonParameters {
+    CommonParameters {
+        public_key_use: Some(PublicKeyUse::Signature),
+        key_algorithm: Some(algorithm),
+        key_id: Some(kid.to_string()),
+        ..Default::default()
+    }
+}
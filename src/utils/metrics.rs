@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+
+use axum::http::{Method, StatusCode};
+use metrics::{
+    Unit, counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram,
+};
+use metrics_exporter_prometheus::PrometheusHandle;
+use sea_orm::DatabaseConnection;
+use tokio::task::JoinHandle;
+
+/// Which login endpoint an attempt went through.
+#[derive(Debug, Clone, Copy)]
+pub enum LoginStep {
+    Password,
+    Mfa,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LoginOutcome {
+    Success,
+    /// Correct password, but a second factor is still needed.
+    MfaRequired,
+    Failure,
+    Throttled,
+}
+
+impl LoginStep {
+    fn as_str(self) -> &'static str {
+        match self {
+            LoginStep::Password => "password",
+            LoginStep::Mfa => "mfa",
+        }
+    }
+}
+
+impl LoginOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            LoginOutcome::Success => "success",
+            LoginOutcome::MfaRequired => "mfa_required",
+            LoginOutcome::Failure => "failure",
+            LoginOutcome::Throttled => "throttled",
+        }
+    }
+}
+
+pub fn describe_metrics() {
+    describe_counter!("http_requests_total", "HTTP requests handled");
+    describe_histogram!(
+        "http_request_duration_seconds",
+        Unit::Seconds,
+        "Time from receiving a request to sending the response headers"
+    );
+    describe_counter!("app_errors_total", "Error responses, by AppError variant");
+    describe_counter!("auth_login_attempts_total", "Login attempts by outcome");
+    describe_gauge!(
+        "db_pool_connections",
+        "Database pool connections, by state (in_use or idle)"
+    );
+    describe_gauge!("db_pool_max_connections", "Database pool size limit");
+    describe_histogram!(
+        "db_pool_acquire_duration_seconds",
+        Unit::Seconds,
+        "Time a probe waited to check out a pooled connection"
+    );
+}
+
+/// `route` is the matched route template, so ids in paths do not explode
+/// the label cardinality.
+pub fn record_http_request(method: &Method, route: &str, status: StatusCode, elapsed: Duration) {
+    let labels = [
+        ("method", method.to_string()),
+        ("route", route.to_string()),
+        ("status", status.as_u16().to_string()),
+    ];
+    counter!("http_requests_total", &labels).increment(1);
+    histogram!("http_request_duration_seconds", &labels).record(elapsed.as_secs_f64());
+}
+
+pub fn record_app_error(kind: &'static str) {
+    counter!("app_errors_total", "kind" => kind).increment(1);
+}
+
+pub fn record_login(step: LoginStep, outcome: LoginOutcome) {
+    counter!(
+        "auth_login_attempts_total",
+        "step" => step.as_str(),
+        "outcome" => outcome.as_str(),
+    )
+    .increment(1);
+}
+
+/// Samples the connection pool every `interval`. The wait time is measured
+/// by checking out a connection, so it reflects what a request would see.
+/// Also runs the exporter's upkeep, which drains histogram buffers.
+pub fn spawn_pool_sampler(
+    db: DatabaseConnection,
+    handle: PrometheusHandle,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let pool = db.get_postgres_connection_pool();
+        gauge!("db_pool_max_connections").set(pool.options().get_max_connections() as f64);
+
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+
+            let size = pool.size();
+            let idle = pool.num_idle() as u32;
+            gauge!("db_pool_connections", "state" => "idle").set(idle as f64);
+            gauge!("db_pool_connections", "state" => "in_use")
+                .set(size.saturating_sub(idle) as f64);
+
+            let started = Instant::now();
+            match pool.acquire().await {
+                Ok(_connection) => histogram!("db_pool_acquire_duration_seconds")
+                    .record(started.elapsed().as_secs_f64()),
+                Err(e) => tracing::warn!("Failed to sample the connection pool: {e}"),
+            }
+
+            handle.run_upkeep();
+        }
+    })
+}
@@ -1,2 +1,11 @@
+pub mod api_key_cache;
 pub mod auth;
 pub mod hash;
+pub mod jwt_key;
+pub mod metrics;
+pub mod request_id;
+pub mod revocation;
+pub mod shutdown;
+pub mod throttle;
+pub mod token;
+pub mod totp;
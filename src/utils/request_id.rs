@@ -0,0 +1,77 @@
+use axum::http::{HeaderMap, HeaderName};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// The caller's `X-Request-Id` when it is safe to echo back, so ids from a
+/// gateway carry through; otherwise a fresh one.
+pub fn from_headers(headers: &HeaderMap) -> String {
+    headers
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// Runs `future` with `id` as the current request id.
+pub async fn scope<F: Future>(id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(id, future).await
+}
+
+/// The id of the request being handled, if any.
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_propagates_valid_request_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("gw-1234.abc"));
+
+        assert_eq!(from_headers(&headers), "gw-1234.abc");
+    }
+
+    #[test]
+    fn test_replaces_missing_or_unsafe_request_id() {
+        let mut headers = HeaderMap::new();
+        assert!(Uuid::parse_str(&from_headers(&headers)).is_ok());
+
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("a b\"c"));
+        assert!(Uuid::parse_str(&from_headers(&headers)).is_ok());
+
+        let long = "a".repeat(MAX_REQUEST_ID_LENGTH + 1);
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_str(&long).unwrap());
+        assert!(Uuid::parse_str(&from_headers(&headers)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_current_is_scoped_to_the_request() {
+        assert_eq!(current(), None);
+
+        let inside = scope("req-1".to_string(), async { current() }).await;
+
+        assert_eq!(inside, Some("req-1".to_string()));
+        assert_eq!(current(), None);
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use chrono::Utc;
+
+#[derive(Clone, Copy)]
+struct SessionCutoff {
+    issued_before: i64,
+    expires_at: i64,
+}
+
+#[derive(Clone, Default)]
+pub struct RevocationCache {
+    entries: Arc<RwLock<HashMap<String, i64>>>,
+    session_cutoffs: Arc<RwLock<HashMap<i32, SessionCutoff>>>,
+}
+
+impl RevocationCache {
+    pub fn insert(&self, jti: String, expires_at: i64) {
+        let mut entries = self.entries.write().expect("revocation cache poisoned");
+        entries.insert(jti, expires_at);
+    }
+
+    pub fn is_revoked(&self, jti: &str) -> bool {
+        let entries = self.entries.read().expect("revocation cache poisoned");
+        entries.contains_key(jti)
+    }
+
+    pub fn extend<I>(&self, revoked: I)
+    where
+        I: IntoIterator<Item = (String, i64)>,
+    {
+        let now = Utc::now().timestamp();
+        let mut entries = self.entries.write().expect("revocation cache poisoned");
+        entries.extend(
+            revoked
+                .into_iter()
+                .filter(|(_, expires_at)| *expires_at > now),
+        );
+    }
+
+    /// Revokes every token of `user_id` issued before `issued_before`. The entry
+    /// can be dropped at `expires_at`, once all such tokens have expired anyway.
+    pub fn revoke_sessions_before(&self, user_id: i32, issued_before: i64, expires_at: i64) {
+        let mut cutoffs = self
+            .session_cutoffs
+            .write()
+            .expect("revocation cache poisoned");
+        let cutoff = cutoffs.entry(user_id).or_insert(SessionCutoff {
+            issued_before,
+            expires_at,
+        });
+        cutoff.issued_before = cutoff.issued_before.max(issued_before);
+        cutoff.expires_at = cutoff.expires_at.max(expires_at);
+    }
+
+    pub fn is_session_revoked(&self, user_id: i32, issued_at: i64) -> bool {
+        let cutoffs = self
+            .session_cutoffs
+            .read()
+            .expect("revocation cache poisoned");
+        cutoffs
+            .get(&user_id)
+            .is_some_and(|cutoff| issued_at < cutoff.issued_before)
+    }
+
+    pub fn prune_expired(&self) -> usize {
+        let now = Utc::now().timestamp();
+
+        let mut entries = self.entries.write().expect("revocation cache poisoned");
+        let before = entries.len();
+        entries.retain(|_, expires_at| *expires_at > now);
+        let pruned = before - entries.len();
+        drop(entries);
+
+        let mut cutoffs = self
+            .session_cutoffs
+            .write()
+            .expect("revocation cache poisoned");
+        let before = cutoffs.len();
+        cutoffs.retain(|_, cutoff| cutoff.expires_at > now);
+
+        pruned + before - cutoffs.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_is_revoked() {
+        let cache = RevocationCache::default();
+        let exp = Utc::now().timestamp() + 60;
+
+        cache.insert("abc".to_string(), exp);
+
+        assert!(cache.is_revoked("abc"));
+        assert!(!cache.is_revoked("def"));
+    }
+
+    #[test]
+    fn test_prune_expired_drops_only_expired_entries() {
+        let cache = RevocationCache::default();
+        let now = Utc::now().timestamp();
+
+        cache.insert("expired".to_string(), now - 1);
+        cache.insert("active".to_string(), now + 60);
+
+        assert_eq!(cache.prune_expired(), 1);
+        assert!(!cache.is_revoked("expired"));
+        assert!(cache.is_revoked("active"));
+    }
+
+    #[test]
+    fn test_extend_skips_expired_entries() {
+        let cache = RevocationCache::default();
+        let now = Utc::now().timestamp();
+
+        cache.insert("local".to_string(), now + 60);
+        cache.extend(vec![
+            ("fresh".to_string(), now + 60),
+            ("expired".to_string(), now - 1),
+        ]);
+
+        assert!(cache.is_revoked("local"));
+        assert!(cache.is_revoked("fresh"));
+        assert!(!cache.is_revoked("expired"));
+    }
+
+    #[test]
+    fn test_session_cutoff_revokes_only_older_tokens() {
+        let cache = RevocationCache::default();
+        let now = Utc::now().timestamp();
+
+        cache.revoke_sessions_before(1, now, now + 60);
+
+        assert!(cache.is_session_revoked(1, now - 10));
+        assert!(!cache.is_session_revoked(1, now));
+        assert!(!cache.is_session_revoked(2, now - 10));
+    }
+
+    #[test]
+    fn test_prune_expired_drops_expired_session_cutoffs() {
+        let cache = RevocationCache::default();
+        let now = Utc::now().timestamp();
+
+        cache.revoke_sessions_before(1, now, now - 1);
+
+        assert_eq!(cache.prune_expired(), 1);
+        assert!(!cache.is_session_revoked(1, now - 10));
+    }
+}
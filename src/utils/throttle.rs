@@ -0,0 +1,383 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+
+/// Keys the memory store tracks at most; past it the least recently
+/// attempted key is evicted, so spraying random emails cannot grow it.
+const MEMORY_STORE_MAX_KEYS: usize = 100_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttemptRecord {
+    /// Attempts counted since the last success or quiet window, including
+    /// ones still in flight.
+    pub failures: u32,
+    pub last_failure_at: i64,
+}
+
+/// Login attempt counters keyed by account or client IP.
+#[async_trait]
+pub trait LoginAttemptStore: Send + Sync {
+    /// Atomically checks `policy` and, unless the key is throttled, counts an
+    /// attempt, so concurrent guesses cannot all pass the check before any of
+    /// them is counted. Returns the seconds to wait when throttled, in which
+    /// case nothing is counted. The count starts over when the previous
+    /// attempt is older than `policy.window_secs`.
+    async fn reserve(&self, key: &str, now: i64, policy: &ThrottlePolicy) -> Result<Option<i64>>;
+
+    /// Takes back one reserved attempt.
+    async fn release(&self, key: &str) -> Result<()>;
+
+    async fn clear(&self, key: &str) -> Result<()>;
+}
+
+#[derive(Default)]
+pub struct MemoryLoginAttemptStore {
+    records: Mutex<MemoryRecords>,
+}
+
+/// Records plus an index of them by last attempt, so the oldest can be
+/// evicted without scanning.
+#[derive(Default)]
+struct MemoryRecords {
+    by_key: HashMap<String, AttemptRecord>,
+    by_age: BTreeSet<(i64, String)>,
+}
+
+impl MemoryRecords {
+    fn remove(&mut self, key: &str) {
+        if let Some(record) = self.by_key.remove(key) {
+            self.by_age
+                .remove(&(record.last_failure_at, key.to_string()));
+        }
+    }
+}
+
+#[async_trait]
+impl LoginAttemptStore for MemoryLoginAttemptStore {
+    async fn reserve(&self, key: &str, now: i64, policy: &ThrottlePolicy) -> Result<Option<i64>> {
+        let mut records = self.records.lock().expect("login attempt store poisoned");
+
+        if !records.by_key.contains_key(key)
+            && records.by_key.len() >= MEMORY_STORE_MAX_KEYS
+            && let Some((_, oldest)) = records.by_age.pop_first()
+        {
+            records.by_key.remove(&oldest);
+        }
+
+        let mut record = records.by_key.get(key).copied().unwrap_or(AttemptRecord {
+            failures: 0,
+            last_failure_at: now,
+        });
+
+        if let Some(wait) = policy.retry_after(&record, now) {
+            return Ok(Some(wait));
+        }
+
+        if now - record.last_failure_at >= policy.window_secs {
+            record.failures = 0;
+        }
+        record.failures += 1;
+
+        records.remove(key);
+        record.last_failure_at = now;
+        records.by_age.insert((now, key.to_string()));
+        records.by_key.insert(key.to_string(), record);
+
+        Ok(None)
+    }
+
+    async fn release(&self, key: &str) -> Result<()> {
+        let mut records = self.records.lock().expect("login attempt store poisoned");
+        if let Some(record) = records.by_key.get_mut(key) {
+            record.failures = record.failures.saturating_sub(1);
+            if record.failures == 0 {
+                records.remove(key);
+            }
+        }
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<()> {
+        let mut records = self.records.lock().expect("login attempt store poisoned");
+        records.remove(key);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ThrottlePolicy {
+    /// Failures allowed before any delay applies.
+    pub free_attempts: u32,
+    pub base_delay_secs: i64,
+    pub max_delay_secs: i64,
+    /// Failures after which the key is locked for `lockout_secs`.
+    pub lockout_threshold: u32,
+    pub lockout_secs: i64,
+    /// Quiet period after which the failure count starts over.
+    pub window_secs: i64,
+}
+
+impl ThrottlePolicy {
+    /// Seconds the caller must wait before the next attempt, if any.
+    pub fn retry_after(&self, record: &AttemptRecord, now: i64) -> Option<i64> {
+        if now - record.last_failure_at >= self.window_secs {
+            return None;
+        }
+
+        let wait = if record.failures >= self.lockout_threshold {
+            self.lockout_secs
+        } else if record.failures > self.free_attempts {
+            let exponent = (record.failures - self.free_attempts - 1).min(30);
+            self.base_delay_secs
+                .saturating_mul(1i64 << exponent)
+                .min(self.max_delay_secs)
+        } else {
+            return None;
+        };
+
+        let remaining = record.last_failure_at + wait - now;
+        (remaining > 0).then_some(remaining)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LoginThrottleConfig {
+    pub account: ThrottlePolicy,
+    pub ip: ThrottlePolicy,
+    /// Read the client IP from `X-Forwarded-For`; only safe behind a trusted proxy.
+    pub trust_proxy_headers: bool,
+}
+
+#[derive(Clone)]
+pub struct LoginThrottle {
+    store: Arc<dyn LoginAttemptStore>,
+    pub config: LoginThrottleConfig,
+}
+
+impl LoginThrottle {
+    pub fn new(store: Arc<dyn LoginAttemptStore>, config: LoginThrottleConfig) -> Self {
+        LoginThrottle { store, config }
+    }
+
+    fn account_key(email: &str) -> String {
+        format!("account:{}", email.trim().to_lowercase())
+    }
+
+    fn ip_key(ip: &str) -> String {
+        format!("ip:{ip}")
+    }
+
+    /// Counts a login attempt against the account and the IP before the
+    /// credentials are checked; it stays counted as a failure unless
+    /// `record_success` follows. Returns the seconds to wait instead when
+    /// either is throttled, in which case nothing is counted.
+    pub async fn reserve(&self, email: &str, ip: Option<&str>) -> Result<Option<i64>> {
+        let now = Utc::now().timestamp();
+        let account_key = Self::account_key(email);
+
+        if let Some(wait) = self
+            .store
+            .reserve(&account_key, now, &self.config.account)
+            .await?
+        {
+            return Ok(Some(wait));
+        }
+
+        if let Some(ip) = ip
+            && let Some(wait) = self
+                .store
+                .reserve(&Self::ip_key(ip), now, &self.config.ip)
+                .await?
+        {
+            self.store.release(&account_key).await?;
+            return Ok(Some(wait));
+        }
+
+        Ok(None)
+    }
+
+    /// Clears the account counter and takes back the IP's reserved attempt,
+    /// so logging into one's own account cannot be used to reset an IP that
+    /// is guessing other passwords.
+    pub async fn record_success(&self, email: &str, ip: Option<&str>) -> Result<()> {
+        self.store.clear(&Self::account_key(email)).await?;
+        self.release_ip(ip).await
+    }
+
+    /// Takes back the IP's reserved attempt only, for a correct password that
+    /// still needs a second factor. The account attempt stays counted, so
+    /// repeating the password step cannot reset second-factor guessing.
+    pub async fn release_ip(&self, ip: Option<&str>) -> Result<()> {
+        if let Some(ip) = ip {
+            self.store.release(&Self::ip_key(ip)).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ThrottlePolicy {
+        ThrottlePolicy {
+            free_attempts: 3,
+            base_delay_secs: 2,
+            max_delay_secs: 30,
+            lockout_threshold: 10,
+            lockout_secs: 900,
+            window_secs: 3600,
+        }
+    }
+
+    fn record(failures: u32, last_failure_at: i64) -> AttemptRecord {
+        AttemptRecord {
+            failures,
+            last_failure_at,
+        }
+    }
+
+    #[test]
+    fn test_retry_after_allows_free_attempts() {
+        assert_eq!(policy().retry_after(&record(3, 100), 100), None);
+    }
+
+    #[test]
+    fn test_retry_after_grows_exponentially_up_to_max() {
+        let p = policy();
+        assert_eq!(p.retry_after(&record(4, 100), 100), Some(2));
+        assert_eq!(p.retry_after(&record(5, 100), 100), Some(4));
+        assert_eq!(p.retry_after(&record(6, 100), 101), Some(7));
+        assert_eq!(p.retry_after(&record(9, 100), 100), Some(30));
+    }
+
+    #[test]
+    fn test_retry_after_locks_out_at_threshold() {
+        let p = policy();
+        assert_eq!(p.retry_after(&record(10, 100), 100), Some(900));
+        assert_eq!(p.retry_after(&record(10, 100), 1000), None);
+    }
+
+    fn failures(store: &MemoryLoginAttemptStore, key: &str) -> Option<u32> {
+        let records = store.records.lock().unwrap();
+        records.by_key.get(key).map(|record| record.failures)
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_reserves_until_throttled() {
+        let store = MemoryLoginAttemptStore::default();
+        let policy = ThrottlePolicy {
+            free_attempts: 2,
+            window_secs: 60,
+            ..policy()
+        };
+
+        assert_eq!(store.reserve("k", 0, &policy).await.unwrap(), None);
+        assert_eq!(store.reserve("k", 0, &policy).await.unwrap(), None);
+        assert_eq!(store.reserve("k", 0, &policy).await.unwrap(), None);
+        assert_eq!(store.reserve("k", 0, &policy).await.unwrap(), Some(2));
+        assert_eq!(failures(&store, "k"), Some(3));
+
+        store.release("k").await.unwrap();
+        assert_eq!(failures(&store, "k"), Some(2));
+
+        assert_eq!(store.reserve("k", 100, &policy).await.unwrap(), None);
+        assert_eq!(failures(&store, "k"), Some(1));
+
+        store.clear("k").await.unwrap();
+        assert_eq!(failures(&store, "k"), None);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_evicts_the_oldest_key_when_full() {
+        let store = MemoryLoginAttemptStore::default();
+        let policy = policy();
+
+        for i in 0..MEMORY_STORE_MAX_KEYS as i64 {
+            store.reserve(&format!("k{i}"), i, &policy).await.unwrap();
+        }
+        // Touching the oldest key makes `k1` the least recently attempted.
+        store.reserve("k0", 1_000_000, &policy).await.unwrap();
+        store.reserve("new", 1_000_000, &policy).await.unwrap();
+
+        let records = store.records.lock().unwrap();
+        assert_eq!(records.by_key.len(), MEMORY_STORE_MAX_KEYS);
+        assert_eq!(records.by_age.len(), MEMORY_STORE_MAX_KEYS);
+        assert!(records.by_key.contains_key("k0"));
+        assert!(!records.by_key.contains_key("k1"));
+        assert!(records.by_key.contains_key("new"));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reservations_are_all_counted() {
+        let throttle = LoginThrottle::new(
+            Arc::new(MemoryLoginAttemptStore::default()),
+            LoginThrottleConfig {
+                account: policy(),
+                ip: policy(),
+                trust_proxy_headers: false,
+            },
+        );
+
+        let handles: Vec<_> = (0..20)
+            .map(|_| {
+                let throttle = throttle.clone();
+                tokio::spawn(async move { throttle.reserve("a@example.com", None).await })
+            })
+            .collect();
+
+        let mut allowed = 0;
+        for handle in handles {
+            if handle.await.unwrap().unwrap().is_none() {
+                allowed += 1;
+            }
+        }
+        assert_eq!(allowed, policy().free_attempts + 1);
+    }
+
+    #[tokio::test]
+    async fn test_login_throttle_tracks_account_and_ip() {
+        let config = LoginThrottleConfig {
+            account: ThrottlePolicy {
+                free_attempts: 1,
+                ..policy()
+            },
+            ip: ThrottlePolicy {
+                free_attempts: 3,
+                ..policy()
+            },
+            trust_proxy_headers: false,
+        };
+        let throttle = LoginThrottle::new(Arc::new(MemoryLoginAttemptStore::default()), config);
+        let ip = Some("10.0.0.1");
+
+        assert_eq!(throttle.reserve("A@example.com", ip).await.unwrap(), None);
+        assert_eq!(throttle.reserve("a@example.com", ip).await.unwrap(), None);
+        assert!(
+            throttle
+                .reserve("a@example.com", None)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        throttle.record_success("a@example.com", ip).await.unwrap();
+        assert_eq!(throttle.reserve("a@example.com", ip).await.unwrap(), None);
+
+        // The IP keeps the attempts it spent on other accounts.
+        assert_eq!(throttle.reserve("b@example.com", ip).await.unwrap(), None);
+        assert_eq!(throttle.reserve("c@example.com", ip).await.unwrap(), None);
+        assert!(
+            throttle
+                .reserve("d@example.com", ip)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}